@@ -4,7 +4,10 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use mirror_airdrop::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, LatestStageResponse, MerkleRootResponse, QueryMsg,
+    AllStagesResponse, ClaimStatusResponse, ClaimantsResponse, ConfigResponse, Cw20HookMsg,
+    ExecuteMsg, InstantiateMsg, LatestStageResponse, MerkleRootResponse, MigrateMsg,
+    PendingOwnerResponse, PendingRootsResponse, QueryMsg, RolesResponse, SignedClaimMsg,
+    StageInfoResponse, StageStatsResponse, VestingStatusResponse,
 };
 
 fn main() {
@@ -15,8 +18,20 @@ fn main() {
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema(&schema_for!(Cw20HookMsg), &out_dir);
+    export_schema(&schema_for!(SignedClaimMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(LatestStageResponse), &out_dir);
     export_schema(&schema_for!(MerkleRootResponse), &out_dir);
+    export_schema(&schema_for!(StageInfoResponse), &out_dir);
+    export_schema(&schema_for!(StageStatsResponse), &out_dir);
     export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(PendingOwnerResponse), &out_dir);
+    export_schema(&schema_for!(RolesResponse), &out_dir);
+    export_schema(&schema_for!(PendingRootsResponse), &out_dir);
+    export_schema(&schema_for!(VestingStatusResponse), &out_dir);
+    export_schema(&schema_for!(ClaimStatusResponse), &out_dir);
+    export_schema(&schema_for!(AllStagesResponse), &out_dir);
+    export_schema(&schema_for!(ClaimantsResponse), &out_dir);
 }
@@ -0,0 +1,116 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use cosmwasm_std::{
+    to_binary, Api, BankMsg, CanonicalAddr, Coin, CosmosMsg, QuerierWrapper, StdResult, Uint128,
+    WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+
+/// Amount of an asset, used to give the budget of every asset of a stage
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+/// Asset paid out by a stage, either a CW20 token or a native coin
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    Token { contract_addr: String },
+    NativeToken { denom: String },
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::NativeToken { denom } => write!(f, "{}", denom),
+            AssetInfo::Token { contract_addr } => write!(f, "{}", contract_addr),
+        }
+    }
+}
+
+impl AssetInfo {
+    pub fn to_raw(&self, api: &dyn Api) -> StdResult<AssetInfoRaw> {
+        match self {
+            AssetInfo::NativeToken { denom } => Ok(AssetInfoRaw::NativeToken {
+                denom: denom.to_string(),
+            }),
+            AssetInfo::Token { contract_addr } => Ok(AssetInfoRaw::Token {
+                contract_addr: api.addr_canonicalize(contract_addr)?,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfoRaw {
+    Token { contract_addr: CanonicalAddr },
+    NativeToken { denom: String },
+}
+
+impl AssetInfoRaw {
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            AssetInfoRaw::NativeToken { denom } => denom.as_bytes(),
+            AssetInfoRaw::Token { contract_addr } => contract_addr.as_slice(),
+        }
+    }
+
+    pub fn to_normal(&self, api: &dyn Api) -> StdResult<AssetInfo> {
+        match self {
+            AssetInfoRaw::NativeToken { denom } => Ok(AssetInfo::NativeToken {
+                denom: denom.to_string(),
+            }),
+            AssetInfoRaw::Token { contract_addr } => Ok(AssetInfo::Token {
+                contract_addr: api.addr_humanize(contract_addr)?.to_string(),
+            }),
+        }
+    }
+
+    pub fn query_balance(
+        &self,
+        querier: &QuerierWrapper,
+        api: &dyn Api,
+        address: String,
+    ) -> StdResult<Uint128> {
+        match self {
+            AssetInfoRaw::NativeToken { denom } => {
+                Ok(querier.query_balance(address, denom)?.amount)
+            }
+            AssetInfoRaw::Token { contract_addr } => {
+                let res: BalanceResponse = querier.query_wasm_smart(
+                    api.addr_humanize(contract_addr)?,
+                    &Cw20QueryMsg::Balance { address },
+                )?;
+
+                Ok(res.balance)
+            }
+        }
+    }
+
+    pub fn transfer_msg(
+        &self,
+        api: &dyn Api,
+        recipient: String,
+        amount: Uint128,
+    ) -> StdResult<CosmosMsg> {
+        match self {
+            AssetInfoRaw::NativeToken { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient,
+                amount: vec![Coin {
+                    denom: denom.to_string(),
+                    amount,
+                }],
+            })),
+            AssetInfoRaw::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: api.addr_humanize(contract_addr)?.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer { recipient, amount })?,
+            })),
+        }
+    }
+}
@@ -2,153 +2,994 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    to_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Uint128, WasmMsg,
+    from_binary, to_binary, to_vec, Addr, Api, Binary, CanonicalAddr, CosmosMsg, Deps, DepsMut,
+    Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128,
 };
+use cw_storage_plus::{Bound, Map, U32Key};
 
+use crate::asset::{Asset, AssetInfo, AssetInfoRaw};
+use crate::error::ContractError;
+use crate::migration::migrate_state;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, QueryMsg,
+    AllStagesResponse, ClaimAuthorization, ClaimRecordResponse, ClaimStatusResponse,
+    ClaimantResponse, ClaimantsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    IsClaimedResponse, LatestStageResponse, MerkleRootResponse, MigrateMsg, PendingOwnerResponse,
+    PendingRootResponse, PendingRootsResponse, QueryMsg, RolesResponse, SignedClaimMsg,
+    StageAssetStats, StageClaim, StageClaimStatus, StageInfoResponse, StageResponse,
+    StageStatsResponse, VestingStatusResponse,
+};
+use crate::state::{
+    activate_pending_root, active_merkle_root, has_role, is_stage_frozen, load_roles,
+    read_claim_index, stage_exists, stage_from_key, ClaimEntry, ClaimRecord, Config, PendingOwner,
+    PendingRoot, Role, Scheduled, VestingSchedule, CLAIM_INDEX, CONFIG, LATEST_STAGE, MERKLE_ROOT,
+    OUTSTANDING_AMOUNT, PENDING_OWNER, PENDING_ROOTS, ROLES, STAGE_AMOUNT, STAGE_AMOUNT_CLAIMED,
+    STAGE_AMOUNT_RELEASED, STAGE_AMOUNT_WITHDRAWN, STAGE_ASSETS, STAGE_CLAIMANTS,
+    STAGE_CLAIMANT_COUNT, STAGE_EXPIRATION, STAGE_FROZEN, STAGE_START, STAGE_VESTING,
+    VESTING_RELEASED,
 };
-use crate::state::{Config, CLAIM_INDEX, CONFIG, LATEST_STAGE, MERKLE_ROOT};
 
-use cw20::Cw20ExecuteMsg;
+use bech32::FromBase32;
+use cw0::Expiration;
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
 use sha3::Digest;
 use std::convert::TryInto;
 
+// version info for migration info
+pub const CONTRACT_NAME: &str = "crate:mirror-airdrop";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     CONFIG.save(
         deps.storage,
         &Config {
             owner: deps.api.addr_canonicalize(&msg.owner)?,
             mirror_token: deps.api.addr_canonicalize(&msg.mirror_token)?,
+            paused: false,
+            paused_stages: vec![],
         },
     )?;
 
-    let stage: u8 = 0;
+    let stage: u32 = 0;
     LATEST_STAGE.save(deps.storage, &stage)?;
 
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig { owner } => update_config(deps, env, info, owner),
-        ExecuteMsg::UpdateMerkleRoot { stage, merkle_root } => {
-            update_merkle_root(deps, env, info, stage, merkle_root)
-        }
-        ExecuteMsg::RegisterMerkleRoot { merkle_root } => {
-            register_merkle_root(deps, env, info, merkle_root)
+        ExecuteMsg::ProposeNewOwner { owner, expiry } => {
+            propose_new_owner(deps, env, info, owner, expiry)
         }
+        ExecuteMsg::AcceptOwnership {} => accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => cancel_ownership_proposal(deps, env, info),
+        ExecuteMsg::Pause { stage } => set_paused(deps, env, info, stage, true),
+        ExecuteMsg::Unpause { stage } => set_paused(deps, env, info, stage, false),
+        ExecuteMsg::GrantRole { address, role } => grant_role(deps, env, info, address, role),
+        ExecuteMsg::RevokeRole { address, role } => revoke_role(deps, env, info, address, role),
+        ExecuteMsg::UpdateMerkleRoot {
+            stage,
+            merkle_root,
+            activation_height,
+        } => update_merkle_root(deps, env, info, stage, merkle_root, activation_height),
+        ExecuteMsg::CancelPendingRoot { stage } => cancel_pending_root(deps, env, info, stage),
+        ExecuteMsg::FreezeStage { stage } => freeze_stage(deps, env, info, stage),
+        ExecuteMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+            start,
+            total_amount,
+            asset_info,
+            assets,
+            activation_height,
+            vesting,
+        } => register_merkle_root(
+            deps,
+            env,
+            info,
+            merkle_root,
+            expiration,
+            start,
+            total_amount,
+            asset_info,
+            assets,
+            activation_height,
+            vesting,
+        ),
         ExecuteMsg::Claim {
             stage,
             amount,
             proof,
-        } => claim(deps, env, info, stage, amount, proof),
+            amounts,
+            authorization,
+        } => claim(
+            deps,
+            env,
+            info,
+            stage,
+            claim_amounts(amount, amounts)?,
+            proof,
+            authorization,
+        ),
+        ExecuteMsg::ClaimFor {
+            stage,
+            address,
+            amount,
+            proof,
+            amounts,
+        } => claim_for(
+            deps,
+            env,
+            info,
+            stage,
+            address,
+            claim_amounts(amount, amounts)?,
+            proof,
+        ),
+        ExecuteMsg::ClaimVested { stage, address } => claim_vested(deps, env, info, stage, address),
+        ExecuteMsg::ClaimMany { claims } => claim_many(deps, env, info, claims),
+        ExecuteMsg::WithdrawUnclaimed { stage, recipient } => {
+            withdraw_unclaimed(deps, env, info, stage, recipient)
+        }
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
     }
 }
 
-pub fn update_merkle_root(
+pub fn set_paused(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    stage: u8,
+    stage: Option<u32>,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::Pauser)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![(
+        "action",
+        if paused { "pause" } else { "unpause" }.to_string(),
+    )];
+
+    match stage {
+        Some(stage) => {
+            if !stage_exists(deps.storage, stage)? {
+                return Err(ContractError::StageNotFound { stage });
+            }
+
+            match (config.paused_stages.binary_search(&stage), paused) {
+                (Err(index), true) => config.paused_stages.insert(index, stage),
+                (Ok(index), false) => {
+                    config.paused_stages.remove(index);
+                }
+                _ => (),
+            }
+            attributes.push(("stage", stage.to_string()));
+        }
+        None => config.paused = paused,
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new().add_attributes(attributes))
+}
+
+pub fn grant_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address_raw = deps.api.addr_canonicalize(&address)?;
+    let mut roles = ROLES
+        .may_load(deps.storage, address_raw.as_slice())?
+        .unwrap_or_default();
+    if !roles.contains(&role) {
+        roles.push(role);
+        ROLES.save(deps.storage, address_raw.as_slice(), &roles)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "grant_role".to_string()),
+        ("address", address),
+        ("role", role.to_string()),
+    ]))
+}
+
+pub fn revoke_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::Admin)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address_raw = deps.api.addr_canonicalize(&address)?;
+    let mut roles = ROLES
+        .may_load(deps.storage, address_raw.as_slice())?
+        .unwrap_or_default();
+    roles.retain(|granted| *granted != role);
+    if roles.is_empty() {
+        ROLES.remove(deps.storage, address_raw.as_slice());
+    } else {
+        ROLES.save(deps.storage, address_raw.as_slice(), &roles)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "revoke_role".to_string()),
+        ("address", address),
+        ("role", role.to_string()),
+    ]))
+}
+
+pub fn update_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u32,
     merkle_root: String,
-) -> StdResult<Response> {
+    activation_height: Option<u64>,
+) -> Result<Response, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::RootPublisher)? {
+        return Err(ContractError::Unauthorized {});
     }
 
-    MERKLE_ROOT.save(deps.storage, &[stage], &merkle_root)?;
+    assert_not_paused(&config, Some(stage))?;
+    if !stage_exists(deps.storage, stage)? {
+        return Err(ContractError::StageNotFound { stage });
+    }
+
+    if is_stage_frozen(deps.storage, stage)? {
+        return Err(ContractError::StageFrozen { stage });
+    }
+
+    // a queued root has to be cancelled before the stage gets another
+    activate_pending_root(deps.storage, &env.block, stage)?;
+    if PENDING_ROOTS
+        .may_load(deps.storage, U32Key::from(stage))?
+        .is_some()
+    {
+        return Err(ContractError::PendingRootExists { stage });
+    }
+
+    let merkle_root = validate_merkle_root(&merkle_root)?;
+    let mut attributes = vec![
+        ("action", "update_merkle_root".to_string()),
+        ("stage", stage.to_string()),
+        ("merkle_root", hex::encode(merkle_root)),
+    ];
+
+    match queue_merkle_root(deps.storage, &env, stage, merkle_root, activation_height)? {
+        Some(activation_height) => {
+            attributes.push(("activation_height", activation_height.to_string()));
+        }
+        None => MERKLE_ROOT.save(deps.storage, U32Key::from(stage), &merkle_root)?,
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Saves the root as the pending root of the stage, active from `activation_height`
+fn queue_merkle_root(
+    storage: &mut dyn Storage,
+    env: &Env,
+    stage: u32,
+    merkle_root: [u8; 32],
+    activation_height: Option<u64>,
+) -> Result<Option<u64>, ContractError> {
+    let activation_height = match activation_height {
+        Some(activation_height) => activation_height,
+        None => return Ok(None),
+    };
+    if activation_height <= env.block.height {
+        return Err(ContractError::InvalidActivationHeight {});
+    }
+
+    PENDING_ROOTS.save(
+        storage,
+        U32Key::from(stage),
+        &PendingRoot {
+            merkle_root,
+            activation_height,
+        },
+    )?;
+
+    Ok(Some(activation_height))
+}
+
+pub fn cancel_pending_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u32,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::RootPublisher)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // roots already active cannot be cancelled
+    activate_pending_root(deps.storage, &env.block, stage)?;
+    if PENDING_ROOTS
+        .may_load(deps.storage, U32Key::from(stage))?
+        .is_none()
+    {
+        return Err(ContractError::NoPendingRoot { stage });
+    }
+    PENDING_ROOTS.remove(deps.storage, U32Key::from(stage));
 
     Ok(Response::new().add_attributes(vec![
-        ("action", "update_merkle_root"),
+        ("action", "cancel_pending_root"),
         ("stage", &stage.to_string()),
-        ("merkle_root", &merkle_root),
     ]))
 }
 
-pub fn update_config(
+pub fn freeze_stage(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    owner: Option<String>,
-) -> StdResult<Response> {
-    let mut config: Config = CONFIG.load(deps.storage)?;
+    stage: u32,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::RootPublisher)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !stage_exists(deps.storage, stage)? {
+        return Err(ContractError::StageNotFound { stage });
+    }
+
+    // the root in place at freezing stays, a queued one is dropped. Stages
+    // still waiting for their first root would be left without any
+    activate_pending_root(deps.storage, &env.block, stage)?;
+    if !MERKLE_ROOT.has(deps.storage, U32Key::from(stage)) {
+        return Err(ContractError::StageNotActive { stage });
+    }
+    PENDING_ROOTS.remove(deps.storage, U32Key::from(stage));
+    STAGE_FROZEN.save(deps.storage, U32Key::from(stage), &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "freeze_stage"),
+        ("stage", &stage.to_string()),
+    ]))
+}
+
+pub fn propose_new_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let expiry = expiry.unwrap_or_default();
+    if expiry.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    // a new proposal replaces the pending one
+    PENDING_OWNER.save(
+        deps.storage,
+        &PendingOwner {
+            owner: deps.api.addr_canonicalize(&owner)?,
+            expiry,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_new_owner".to_string()),
+        ("owner", owner),
+        ("expiry", expiry.to_string()),
+    ]))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoOwnershipProposal {})?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != pending_owner.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
+    if pending_owner.expiry.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {
+            expiry: pending_owner.expiry,
+        });
     }
 
+    let mut config: Config = CONFIG.load(deps.storage)?;
+    config.owner = pending_owner.owner;
     CONFIG.save(deps.storage, &config)?;
-    Ok(Response::new().add_attribute("action", "update_config"))
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_ownership"),
+        ("owner", info.sender.as_str()),
+    ]))
 }
 
-pub fn register_merkle_root(
+pub fn cancel_ownership_proposal(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    merkle_root: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized {});
     }
 
-    let latest_stage: u8 = LATEST_STAGE.load(deps.storage)?;
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoOwnershipProposal {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_proposal"))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_merkle_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    merkle_root: String,
+    expiration: Option<Expiration>,
+    start: Option<Scheduled>,
+    total_amount: Option<Uint128>,
+    asset_info: Option<AssetInfo>,
+    assets: Option<Vec<Asset>>,
+    activation_height: Option<u64>,
+    vesting: Option<VestingSchedule>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::RootPublisher)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let (asset_infos, total_amounts) = match assets {
+        Some(assets) => {
+            if asset_info.is_some() || total_amount.is_some() {
+                return Err(ContractError::InvalidAssets {
+                    reason: "cannot be combined with asset_info or total_amount".to_string(),
+                });
+            }
+
+            let (asset_infos, total_amounts) = validate_assets(deps.as_ref(), assets)?;
+            (Some(asset_infos), Some(total_amounts))
+        }
+        None => (
+            asset_info
+                .map(|asset_info| asset_info.to_raw(deps.api).map(|raw| vec![raw]))
+                .transpose()?,
+            total_amount.map(|total_amount| vec![total_amount]),
+        ),
+    };
+
+    if let Some(total_amounts) = &total_amounts {
+        let stage_asset_infos = asset_infos
+            .clone()
+            .unwrap_or_else(|| vec![mirror_token_asset(&config)]);
+        for (stage_asset_info, total_amount) in stage_asset_infos.iter().zip(total_amounts) {
+            assert_sufficient_balance(deps.as_ref(), &env, stage_asset_info, *total_amount)?;
+        }
+    }
+
+    store_stage(
+        deps,
+        env,
+        merkle_root,
+        expiration,
+        start,
+        total_amounts,
+        asset_infos,
+        activation_height,
+        vesting,
+    )
+}
+
+/// Converts the assets of a multi-asset stage, rejecting empty
+/// or repeated asset lists
+fn validate_assets(
+    deps: Deps,
+    assets: Vec<Asset>,
+) -> Result<(Vec<AssetInfoRaw>, Vec<Uint128>), ContractError> {
+    if assets.is_empty() {
+        return Err(ContractError::InvalidAssets {
+            reason: "no asset given".to_string(),
+        });
+    }
+
+    let mut asset_infos: Vec<AssetInfoRaw> = vec![];
+    let mut total_amounts: Vec<Uint128> = vec![];
+    for asset in assets {
+        let asset_info = asset.info.to_raw(deps.api)?;
+        if asset_infos.contains(&asset_info) {
+            return Err(ContractError::InvalidAssets {
+                reason: format!("duplicate asset {}", asset.info),
+            });
+        }
+
+        asset_infos.push(asset_info);
+        total_amounts.push(asset.amount);
+    }
+
+    Ok((asset_infos, total_amounts))
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // only mirror token contract can execute this message
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.mirror_token {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::RegisterMerkleRoot {
+            merkle_root,
+            expiration,
+            start,
+            activation_height,
+            vesting,
+        } => {
+            let sender = deps.api.addr_canonicalize(&cw20_msg.sender)?;
+            if !has_role(deps.storage, &config, &sender, Role::RootPublisher)? {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            // the received tokens are the stage budget
+            store_stage(
+                deps,
+                env,
+                merkle_root,
+                expiration,
+                start,
+                Some(vec![cw20_msg.amount]),
+                None,
+                activation_height,
+                vesting,
+            )
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn store_stage(
+    deps: DepsMut,
+    env: Env,
+    merkle_root: String,
+    expiration: Option<Expiration>,
+    start: Option<Scheduled>,
+    total_amounts: Option<Vec<Uint128>>,
+    asset_infos: Option<Vec<AssetInfoRaw>>,
+    activation_height: Option<u64>,
+    vesting: Option<VestingSchedule>,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_not_paused(&config, None)?;
+
+    let merkle_root = validate_merkle_root(&merkle_root)?;
+    if let Some(expiration) = expiration {
+        if expiration.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+    }
+    if let Some(vesting) = &vesting {
+        validate_vesting(vesting)?;
+    }
+
+    let latest_stage: u32 = LATEST_STAGE.load(deps.storage)?;
     let stage = latest_stage + 1;
 
-    MERKLE_ROOT.save(deps.storage, &[stage], &merkle_root)?;
+    let mut attributes = vec![
+        ("action", "register_merkle_root".to_string()),
+        ("stage", stage.to_string()),
+        ("merkle_root", hex::encode(merkle_root)),
+    ];
+
+    match queue_merkle_root(deps.storage, &env, stage, merkle_root, activation_height)? {
+        Some(activation_height) => {
+            attributes.push(("activation_height", activation_height.to_string()));
+        }
+        None => MERKLE_ROOT.save(deps.storage, U32Key::from(stage), &merkle_root)?,
+    }
     LATEST_STAGE.save(deps.storage, &stage)?;
 
-    Ok(Response::new().add_attributes(vec![
-        ("action", "register_merkle_root"),
-        ("stage", &stage.to_string()),
-        ("merkle_root", &merkle_root),
-    ]))
+    if let Some(expiration) = expiration {
+        STAGE_EXPIRATION.save(deps.storage, U32Key::from(stage), &expiration)?;
+        attributes.push(("expiration", expiration.to_string()));
+    }
+
+    if let Some(start) = start {
+        STAGE_START.save(deps.storage, U32Key::from(stage), &start)?;
+        attributes.push(("start", start.to_string()));
+    }
+
+    if let Some(total_amounts) = total_amounts {
+        STAGE_AMOUNT.save(deps.storage, U32Key::from(stage), &total_amounts)?;
+        let stage_asset_infos = asset_infos
+            .clone()
+            .unwrap_or_else(|| vec![mirror_token_asset(&config)]);
+        add_outstanding_amounts(deps.storage, &stage_asset_infos, &total_amounts)?;
+        attributes.push(("total_amount", join(&total_amounts)));
+    }
+
+    if let Some(asset_infos) = &asset_infos {
+        STAGE_ASSETS.save(deps.storage, U32Key::from(stage), asset_infos)?;
+        attributes.push((
+            "asset_info",
+            join(
+                &asset_infos
+                    .iter()
+                    .map(|asset_info| asset_info.to_normal(deps.api))
+                    .collect::<StdResult<Vec<AssetInfo>>>()?,
+            ),
+        ));
+    }
+
+    if let Some(vesting) = vesting {
+        STAGE_VESTING.save(deps.storage, U32Key::from(stage), &vesting)?;
+        attributes.push((
+            "vesting",
+            format!("{}-{}-{}", vesting.start, vesting.cliff, vesting.end),
+        ));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Requires `start <= cliff <= end` and a non-empty vesting period
+fn validate_vesting(vesting: &VestingSchedule) -> Result<(), ContractError> {
+    if vesting.start >= vesting.end {
+        return Err(ContractError::InvalidVesting {
+            reason: "start must be before end".to_string(),
+        });
+    }
+    if vesting.cliff < vesting.start || vesting.cliff > vesting.end {
+        return Err(ContractError::InvalidVesting {
+            reason: "cliff must be between start and end".to_string(),
+        });
+    }
+
+    Ok(())
 }
 
 pub fn claim(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    stage: u8,
-    amount: Uint128,
+    stage: u32,
+    amounts: Vec<Uint128>,
     proof: Vec<String>,
-) -> StdResult<Response> {
+    authorization: Option<ClaimAuthorization>,
+) -> Result<Response, ContractError> {
+    let (address, recipient) = match authorization {
+        Some(authorization) => {
+            verify_authorization(deps.as_ref(), &env, stage, &amounts, authorization)?
+        }
+        None => (info.sender.clone(), info.sender),
+    };
+
+    let (asset_infos, released_amounts) = claim_allocation(
+        deps.branch(),
+        &env,
+        stage,
+        &address,
+        &recipient,
+        &amounts,
+        proof,
+    )?;
+    let messages = transfer_msgs(
+        deps.api,
+        &asset_infos,
+        recipient.to_string(),
+        released_amounts.as_ref().unwrap_or(&amounts),
+    )?;
+
+    let mut attributes = vec![
+        ("action", "claim".to_string()),
+        ("stage", stage.to_string()),
+        ("address", address.to_string()),
+    ];
+    if recipient != address {
+        attributes.push(("recipient", recipient.to_string()));
+    }
+    attributes.push(("amount", join(&amounts)));
+    if let Some(released_amounts) = released_amounts {
+        attributes.push(("released_amount", join(&released_amounts)));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// Checks the authorization was signed by the key of its address for this
+/// very claim, returning the address and the recipient of the allocation
+fn verify_authorization(
+    deps: Deps,
+    env: &Env,
+    stage: u32,
+    amounts: &[Uint128],
+    authorization: ClaimAuthorization,
+) -> Result<(Addr, Addr), ContractError> {
+    let address = deps.api.addr_validate(&authorization.address)?;
+    let recipient = deps.api.addr_validate(&authorization.recipient)?;
+
+    if !pub_key_matches(address.as_str(), authorization.pub_key.as_slice()) {
+        return Err(ContractError::PubKeyMismatch {
+            address: address.to_string(),
+        });
+    }
+
+    let signed_msg = to_vec(&SignedClaimMsg {
+        contract: env.contract.address.to_string(),
+        stage,
+        recipient: recipient.to_string(),
+        amount: join(amounts),
+    })?;
+    let hash = sha2::Sha256::digest(&signed_msg);
+    if !deps
+        .api
+        .secp256k1_verify(
+            &hash,
+            authorization.signature.as_slice(),
+            authorization.pub_key.as_slice(),
+        )
+        .map_err(StdError::from)?
+    {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    Ok((address, recipient))
+}
+
+/// Checks a bech32 address was derived from the public key,
+/// as ripemd160(sha256(pub_key))
+fn pub_key_matches(address: &str, pub_key: &[u8]) -> bool {
+    let address_bytes = match bech32::decode(address)
+        .ok()
+        .and_then(|(_, data, _)| Vec::<u8>::from_base32(&data).ok())
+    {
+        Some(address_bytes) => address_bytes,
+        None => return false,
+    };
+
+    ripemd160::Ripemd160::digest(&sha2::Sha256::digest(pub_key)).as_slice() == address_bytes
+}
+
+/// Claims the allocation of `address`, which receives the tokens
+/// whoever submits the proof
+pub fn claim_for(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u32,
+    address: String,
+    amounts: Vec<Uint128>,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let (asset_infos, released_amounts) = claim_allocation(
+        deps.branch(),
+        &env,
+        stage,
+        &address,
+        &address,
+        &amounts,
+        proof,
+    )?;
+    let messages = transfer_msgs(
+        deps.api,
+        &asset_infos,
+        address.to_string(),
+        released_amounts.as_ref().unwrap_or(&amounts),
+    )?;
+
+    let mut attributes = vec![
+        ("action", "claim_for".to_string()),
+        ("stage", stage.to_string()),
+        ("address", address.to_string()),
+        ("sender", info.sender.to_string()),
+        ("amount", join(&amounts)),
+    ];
+    if let Some(released_amounts) = released_amounts {
+        attributes.push(("released_amount", join(&released_amounts)));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// Claims several stages at once, sending a single transfer per asset.
+/// Fails as a whole if any of the claims is rejected
+pub fn claim_many(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    claims: Vec<StageClaim>,
+) -> Result<Response, ContractError> {
+    if claims.is_empty() {
+        return Err(ContractError::NoClaims {});
+    }
+
+    let mut stages: Vec<u32> = vec![];
+    let mut asset_infos: Vec<AssetInfoRaw> = vec![];
+    let mut amounts: Vec<Uint128> = vec![];
+    for claim in claims {
+        let claim_amounts = claim_amounts(claim.amount, claim.amounts)?;
+        let (claim_asset_infos, released_amounts) = claim_allocation(
+            deps.branch(),
+            &env,
+            claim.stage,
+            &info.sender,
+            &info.sender,
+            &claim_amounts,
+            claim.proof,
+        )?;
+
+        // sum up the amounts paid in the same asset
+        let paid_amounts = released_amounts.unwrap_or(claim_amounts);
+        for (asset_info, amount) in claim_asset_infos.into_iter().zip(paid_amounts) {
+            match asset_infos.iter().position(|a| *a == asset_info) {
+                Some(index) => {
+                    amounts[index] = amounts[index].checked_add(amount).map_err(StdError::from)?
+                }
+                None => {
+                    asset_infos.push(asset_info);
+                    amounts.push(amount);
+                }
+            }
+        }
+
+        stages.push(claim.stage);
+    }
+
+    Ok(Response::new()
+        .add_messages(transfer_msgs(
+            deps.api,
+            &asset_infos,
+            info.sender.to_string(),
+            &amounts,
+        )?)
+        .add_attributes(vec![
+            ("action", "claim_many"),
+            ("address", info.sender.as_str()),
+            ("stages", &join(&stages)),
+            ("amount", &join(&amounts)),
+        ]))
+}
+
+/// Takes the claimed amounts from either `amount` or `amounts`,
+/// rejecting claims giving both or neither
+fn claim_amounts(
+    amount: Option<Uint128>,
+    amounts: Option<Vec<Uint128>>,
+) -> Result<Vec<Uint128>, ContractError> {
+    match (amount, amounts) {
+        (Some(amount), None) => Ok(vec![amount]),
+        (None, Some(amounts)) => Ok(amounts),
+        (Some(_), Some(_)) => Err(ContractError::InvalidAmounts {
+            reason: "amount cannot be combined with amounts".to_string(),
+        }),
+        (None, None) => Err(ContractError::InvalidAmounts {
+            reason: "no amount given".to_string(),
+        }),
+    }
+}
+
+/// Verifies the leaf of `address` and records its claim paid out to
+/// `recipient`, returning the stage assets the amounts are paid in and,
+/// for vesting stages, the vested part released now
+fn claim_allocation(
+    deps: DepsMut,
+    env: &Env,
+    stage: u32,
+    address: &Addr,
+    recipient: &Addr,
+    amounts: &[Uint128],
+    proof: Vec<String>,
+) -> Result<(Vec<AssetInfoRaw>, Option<Vec<Uint128>>), ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let merkle_root: String = MERKLE_ROOT.load(deps.storage, &[stage])?;
+    assert_not_paused(&config, Some(stage))?;
 
-    let user_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !stage_exists(deps.storage, stage)? {
+        return Err(ContractError::StageNotFound { stage });
+    }
+
+    // claims only verify against active roots
+    activate_pending_root(deps.storage, &env.block, stage)?;
+    let merkle_root: [u8; 32] = MERKLE_ROOT
+        .may_load(deps.storage, U32Key::from(stage))?
+        .ok_or(ContractError::StageNotActive { stage })?;
+
+    if let Some(start) = STAGE_START.may_load(deps.storage, U32Key::from(stage))? {
+        if !start.is_triggered(&env.block) {
+            return Err(ContractError::StageNotStarted { stage, start });
+        }
+    }
+
+    let expiration = STAGE_EXPIRATION
+        .may_load(deps.storage, U32Key::from(stage))?
+        .unwrap_or_default();
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::StageExpired { stage, expiration });
+    }
+
+    let user_raw = deps.api.addr_canonicalize(address.as_str())?;
 
     // If user claimed target stage, return err
-    if CLAIM_INDEX
-        .may_load(deps.storage, (user_raw.as_slice(), &[stage]))?
-        .unwrap_or(false)
-    {
-        return Err(StdError::generic_err("already claimed"));
+    if read_claim_index(deps.storage, user_raw.as_slice(), stage)?.is_some() {
+        return Err(ContractError::AlreadyClaimed {
+            stage,
+            address: address.to_string(),
+        });
     }
 
-    let user_input: String = info.sender.to_string() + &amount.to_string();
+    let asset_infos = load_stage_assets(deps.storage, &config, stage)?;
+    if amounts.len() != asset_infos.len() {
+        return Err(ContractError::AssetCountMismatch {
+            stage,
+            expected: asset_infos.len(),
+            got: amounts.len(),
+        });
+    }
+
+    // leaves commit to the amounts of every stage asset, comma separated
+    let user_input: String = address.to_string() + &join(amounts);
     let mut hash: [u8; 32] = sha3::Keccak256::digest(user_input.as_bytes())
         .as_slice()
         .try_into()
         .expect("Wrong length");
 
-    for p in proof {
-        let mut proof_buf: [u8; 32] = [0; 32];
-        hex::decode_to_slice(p, &mut proof_buf).unwrap();
+    for (index, p) in proof.iter().enumerate() {
+        let proof_buf =
+            decode_hash(p).map_err(|reason| ContractError::InvalidHexProof { index, reason })?;
         hash = if bytes_cmp(hash, proof_buf) == std::cmp::Ordering::Less {
             sha3::Keccak256::digest(&[hash, proof_buf].concat())
                 .as_slice()
@@ -162,32 +1003,411 @@ pub fn claim(
         };
     }
 
-    let mut root_buf: [u8; 32] = [0; 32];
-    hex::decode_to_slice(merkle_root, &mut root_buf).unwrap();
-    if root_buf != hash {
-        return Err(StdError::generic_err("Verification is failed"));
+    if merkle_root != hash {
+        return Err(ContractError::InvalidProof {});
+    }
+
+    let mut claimed_amounts =
+        load_stage_amounts(deps.storage, STAGE_AMOUNT_CLAIMED, stage, amounts.len())?;
+    let total_amounts = STAGE_AMOUNT.may_load(deps.storage, U32Key::from(stage))?;
+    if let Some(total_amounts) = &total_amounts {
+        for ((amount, claimed_amount), total_amount) in
+            amounts.iter().zip(&claimed_amounts).zip(total_amounts)
+        {
+            let remaining = total_amount.saturating_sub(*claimed_amount);
+            if *amount > remaining {
+                return Err(ContractError::StageAmountExceeded { stage, remaining });
+            }
+        }
+    }
+
+    for (claimed_amount, amount) in claimed_amounts.iter_mut().zip(amounts) {
+        *claimed_amount = claimed_amount
+            .checked_add(*amount)
+            .map_err(StdError::from)?;
     }
 
+    let record = ClaimRecord {
+        amounts: amounts.to_vec(),
+        height: env.block.height,
+        time: env.block.time,
+        recipient: deps.api.addr_canonicalize(recipient.as_str())?,
+    };
+
     // Update claim index to the current stage
-    CLAIM_INDEX.save(deps.storage, (user_raw.as_slice(), &[stage]), &true)?;
+    CLAIM_INDEX.save(
+        deps.storage,
+        (user_raw.as_slice(), U32Key::from(stage)),
+        &record,
+    )?;
+    STAGE_AMOUNT_CLAIMED.save(deps.storage, U32Key::from(stage), &claimed_amounts)?;
+    STAGE_CLAIMANT_COUNT.update(deps.storage, U32Key::from(stage), |count| -> StdResult<_> {
+        Ok(count.unwrap_or_default() + 1)
+    })?;
+    // the claim froze the stage, so a root still queued never activates
+    PENDING_ROOTS.remove(deps.storage, U32Key::from(stage));
+    STAGE_CLAIMANTS.save(
+        deps.storage,
+        (U32Key::from(stage), user_raw.as_slice()),
+        &Empty {},
+    )?;
+
+    let released_amounts = match STAGE_VESTING.may_load(deps.storage, U32Key::from(stage))? {
+        Some(vesting) => Some(release_vested(
+            deps.storage,
+            env,
+            stage,
+            &user_raw,
+            &vesting,
+            amounts,
+        )?),
+        None => None,
+    };
+    if total_amounts.is_some() {
+        sub_outstanding_amounts(
+            deps.storage,
+            &asset_infos,
+            released_amounts.as_deref().unwrap_or(amounts),
+        )?;
+    }
+
+    Ok((asset_infos, released_amounts))
+}
+
+/// Sends the part of the allocation of `address` vested since the last release
+/// to the recipient recorded by its claim, whoever submits it. Redirected claims
+/// keep vesting even though their snapshot address cannot send transactions
+pub fn claim_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u32,
+    address: String,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_not_paused(&config, Some(stage))?;
+
+    let vesting = STAGE_VESTING
+        .may_load(deps.storage, U32Key::from(stage))?
+        .ok_or(ContractError::NoVesting { stage })?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let user_raw = deps.api.addr_canonicalize(address.as_str())?;
+    let record = match read_claim_index(deps.storage, user_raw.as_slice(), stage)? {
+        Some(ClaimEntry::Recorded(record)) => record,
+        _ => {
+            return Err(ContractError::NotClaimed {
+                stage,
+                address: address.to_string(),
+            })
+        }
+    };
+
+    let released_amounts = release_vested(
+        deps.storage,
+        &env,
+        stage,
+        &user_raw,
+        &vesting,
+        &record.amounts,
+    )?;
+    if released_amounts.iter().all(|amount| amount.is_zero()) {
+        return Err(ContractError::NothingVested { stage });
+    }
+
+    let asset_infos = load_stage_assets(deps.storage, &config, stage)?;
+    if STAGE_AMOUNT.has(deps.storage, U32Key::from(stage)) {
+        sub_outstanding_amounts(deps.storage, &asset_infos, &released_amounts)?;
+    }
+
+    let recipient = deps.api.addr_humanize(&record.recipient)?;
+    let messages = transfer_msgs(
+        deps.api,
+        &asset_infos,
+        recipient.to_string(),
+        &released_amounts,
+    )?;
+
+    let mut attributes = vec![
+        ("action", "claim_vested".to_string()),
+        ("stage", stage.to_string()),
+        ("address", address.to_string()),
+        ("sender", info.sender.to_string()),
+    ];
+    if recipient != address {
+        attributes.push(("recipient", recipient.to_string()));
+    }
+    attributes.push(("amount", join(&released_amounts)));
 
     Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: deps.api.addr_humanize(&config.mirror_token)?.to_string(),
-            funds: vec![],
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount,
-            })?,
-        }))
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// Records the release of what vested from `amounts` and was not sent yet,
+/// returning the amounts to send
+fn release_vested(
+    storage: &mut dyn Storage,
+    env: &Env,
+    stage: u32,
+    user_raw: &CanonicalAddr,
+    vesting: &VestingSchedule,
+    amounts: &[Uint128],
+) -> Result<Vec<Uint128>, ContractError> {
+    let mut released_amounts = VESTING_RELEASED
+        .may_load(storage, (U32Key::from(stage), user_raw.as_slice()))?
+        .unwrap_or_else(|| vec![Uint128::zero(); amounts.len()]);
+    let mut stage_released_amounts =
+        load_stage_amounts(storage, STAGE_AMOUNT_RELEASED, stage, amounts.len())?;
+
+    let mut releasable_amounts: Vec<Uint128> = vec![];
+    for ((amount, released_amount), stage_released_amount) in amounts
+        .iter()
+        .zip(released_amounts.iter_mut())
+        .zip(stage_released_amounts.iter_mut())
+    {
+        let releasable = vesting
+            .vested_amount(*amount, env.block.time)
+            .saturating_sub(*released_amount);
+        *released_amount += releasable;
+        *stage_released_amount = stage_released_amount
+            .checked_add(releasable)
+            .map_err(StdError::from)?;
+        releasable_amounts.push(releasable);
+    }
+
+    VESTING_RELEASED.save(
+        storage,
+        (U32Key::from(stage), user_raw.as_slice()),
+        &released_amounts,
+    )?;
+    STAGE_AMOUNT_RELEASED.save(storage, U32Key::from(stage), &stage_released_amounts)?;
+
+    Ok(releasable_amounts)
+}
+
+pub fn withdraw_unclaimed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u32,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let sender = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if !has_role(deps.storage, &config, &sender, Role::Treasurer)? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if !stage_exists(deps.storage, stage)? {
+        return Err(ContractError::StageNotFound { stage });
+    }
+
+    let expiration = STAGE_EXPIRATION
+        .may_load(deps.storage, U32Key::from(stage))?
+        .unwrap_or_default();
+    if !expiration.is_expired(&env.block) {
+        return Err(ContractError::StageNotExpired { stage, expiration });
+    }
+
+    let total_amounts = STAGE_AMOUNT
+        .may_load(deps.storage, U32Key::from(stage))?
+        .ok_or(ContractError::StageAmountNotSet { stage })?;
+    let claimed_amounts = load_stage_amounts(
+        deps.storage,
+        STAGE_AMOUNT_CLAIMED,
+        stage,
+        total_amounts.len(),
+    )?;
+    let mut withdrawn_amounts = load_stage_amounts(
+        deps.storage,
+        STAGE_AMOUNT_WITHDRAWN,
+        stage,
+        total_amounts.len(),
+    )?;
+
+    let amounts: Vec<Uint128> = total_amounts
+        .iter()
+        .zip(&claimed_amounts)
+        .zip(&withdrawn_amounts)
+        .map(|((total_amount, claimed_amount), withdrawn_amount)| {
+            total_amount
+                .saturating_sub(*claimed_amount)
+                .saturating_sub(*withdrawn_amount)
+        })
+        .collect();
+    if amounts.iter().all(|amount| amount.is_zero()) {
+        return Err(ContractError::NothingToWithdraw { stage });
+    }
+
+    for (withdrawn_amount, amount) in withdrawn_amounts.iter_mut().zip(&amounts) {
+        *withdrawn_amount += *amount;
+    }
+    STAGE_AMOUNT_WITHDRAWN.save(deps.storage, U32Key::from(stage), &withdrawn_amounts)?;
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let asset_infos = load_stage_assets(deps.storage, &config, stage)?;
+    sub_outstanding_amounts(deps.storage, &asset_infos, &amounts)?;
+    Ok(Response::new()
+        .add_messages(transfer_msgs(
+            deps.api,
+            &asset_infos,
+            recipient.to_string(),
+            &amounts,
+        )?)
         .add_attributes(vec![
-            ("action", "claim"),
+            ("action", "withdraw_unclaimed"),
             ("stage", &stage.to_string()),
-            ("address", info.sender.as_str()),
-            ("amount", &amount.to_string()),
+            ("recipient", recipient.as_str()),
+            ("amount", &join(&amounts)),
         ]))
 }
 
+/// Builds one transfer per asset, skipping the assets with nothing to send
+fn transfer_msgs(
+    api: &dyn Api,
+    asset_infos: &[AssetInfoRaw],
+    recipient: String,
+    amounts: &[Uint128],
+) -> StdResult<Vec<CosmosMsg>> {
+    asset_infos
+        .iter()
+        .zip(amounts)
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(asset_info, amount)| asset_info.transfer_msg(api, recipient.clone(), *amount))
+        .collect()
+}
+
+fn load_stage_assets(
+    storage: &dyn Storage,
+    config: &Config,
+    stage: u32,
+) -> StdResult<Vec<AssetInfoRaw>> {
+    Ok(STAGE_ASSETS
+        .may_load(storage, U32Key::from(stage))?
+        .unwrap_or_else(|| vec![mirror_token_asset(config)]))
+}
+
+/// Loads per asset amounts of a stage, all zero when nothing was stored yet
+fn load_stage_amounts(
+    storage: &dyn Storage,
+    amounts: Map<U32Key, Vec<Uint128>>,
+    stage: u32,
+    asset_count: usize,
+) -> StdResult<Vec<Uint128>> {
+    Ok(amounts
+        .may_load(storage, U32Key::from(stage))?
+        .unwrap_or_else(|| vec![Uint128::zero(); asset_count]))
+}
+
+fn join<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Fails if the contract or the given stage is paused
+fn assert_not_paused(config: &Config, stage: Option<u32>) -> Result<(), ContractError> {
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    match stage {
+        Some(stage) if config.paused_stages.contains(&stage) => {
+            Err(ContractError::StagePaused { stage })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn mirror_token_asset(config: &Config) -> AssetInfoRaw {
+    AssetInfoRaw::Token {
+        contract_addr: config.mirror_token.clone(),
+    }
+}
+
+/// Checks the contract holds `amount` of the asset on top of the amount
+/// still owed by the previously registered stages paying the same asset
+fn assert_sufficient_balance(
+    deps: Deps,
+    env: &Env,
+    asset_info: &AssetInfoRaw,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let balance =
+        asset_info.query_balance(&deps.querier, deps.api, env.contract.address.to_string())?;
+
+    let outstanding_amount = OUTSTANDING_AMOUNT
+        .may_load(deps.storage, asset_info.as_bytes())?
+        .unwrap_or_default();
+    let available = balance.saturating_sub(outstanding_amount);
+    if available < amount {
+        return Err(ContractError::InsufficientFunds {
+            required: amount,
+            available,
+        });
+    }
+
+    Ok(())
+}
+
+/// Adds the amounts a stage owes to the per asset outstanding totals
+fn add_outstanding_amounts(
+    storage: &mut dyn Storage,
+    asset_infos: &[AssetInfoRaw],
+    amounts: &[Uint128],
+) -> StdResult<()> {
+    for (asset_info, amount) in asset_infos.iter().zip(amounts) {
+        OUTSTANDING_AMOUNT.update(storage, asset_info.as_bytes(), |outstanding_amount| {
+            outstanding_amount
+                .unwrap_or_default()
+                .checked_add(*amount)
+                .map_err(StdError::from)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Removes paid out or withdrawn amounts from the per asset outstanding totals
+fn sub_outstanding_amounts(
+    storage: &mut dyn Storage,
+    asset_infos: &[AssetInfoRaw],
+    amounts: &[Uint128],
+) -> StdResult<()> {
+    for (asset_info, amount) in asset_infos.iter().zip(amounts) {
+        OUTSTANDING_AMOUNT.update(storage, asset_info.as_bytes(), |outstanding_amount| {
+            StdResult::Ok(
+                outstanding_amount
+                    .unwrap_or_default()
+                    .saturating_sub(*amount),
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Parses a hex encoded merkle root, accepting either case
+pub fn validate_merkle_root(merkle_root: &str) -> Result<[u8; 32], ContractError> {
+    decode_hash(merkle_root).map_err(|reason| ContractError::InvalidMerkleRoot { reason })
+}
+
+/// Decodes a hex encoded 32 bytes hash, describing why the input
+/// was rejected on failure
+fn decode_hash(input: &str) -> Result<[u8; 32], String> {
+    let mut buf: [u8; 32] = [0; 32];
+    match hex::decode_to_slice(input, &mut buf) {
+        Ok(()) => Ok(buf),
+        Err(hex::FromHexError::InvalidStringLength) => {
+            Err(format!("expected 64 hex characters, got {}", input.len()))
+        }
+        Err(err) => Err(err.to_string()),
+    }
+}
+
 fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
     let mut i = 0;
     while i < 32 {
@@ -207,11 +1427,34 @@ fn bytes_cmp(a: [u8; 32], b: [u8; 32]) -> std::cmp::Ordering {
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps, env)?),
+        QueryMsg::PendingRoots { start_after, limit } => {
+            to_binary(&query_pending_roots(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Roles { address } => to_binary(&query_roles(deps, env, address)?),
         QueryMsg::MerkleRoot { stage } => to_binary(&query_merkle_root(deps, env, stage)?),
+        QueryMsg::StageInfo { stage } => to_binary(&query_stage_info(deps, env, stage)?),
+        QueryMsg::StageStats { stage } => to_binary(&query_stage_stats(deps, env, stage)?),
         QueryMsg::LatestStage {} => to_binary(&query_latest_stage(deps, env)?),
         QueryMsg::IsClaimed { stage, address } => {
             to_binary(&query_is_claimed(deps, env, stage, address)?)
         }
+        QueryMsg::VestingStatus { stage, address } => {
+            to_binary(&query_vesting_status(deps, env, stage, address)?)
+        }
+        QueryMsg::AllStages { start_after, limit } => {
+            to_binary(&query_all_stages(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Claimants {
+            stage,
+            start_after,
+            limit,
+        } => to_binary(&query_claimants(deps, env, stage, start_after, limit)?),
+        QueryMsg::ClaimStatus {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_claim_status(deps, env, address, start_after, limit)?),
     }
 }
 
@@ -220,14 +1463,159 @@ pub fn query_config(deps: Deps, _env: Env) -> StdResult<ConfigResponse> {
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         mirror_token: deps.api.addr_humanize(&state.mirror_token)?.to_string(),
+        paused: state.paused,
+        paused_stages: state.paused_stages,
     };
 
     Ok(resp)
 }
 
-pub fn query_merkle_root(deps: Deps, _env: Env, stage: u8) -> StdResult<MerkleRootResponse> {
-    let merkle_root = MERKLE_ROOT.load(deps.storage, &[stage])?;
-    let resp = MerkleRootResponse { stage, merkle_root };
+pub fn query_pending_owner(deps: Deps, _env: Env) -> StdResult<PendingOwnerResponse> {
+    let resp = match PENDING_OWNER.may_load(deps.storage)? {
+        Some(pending_owner) => PendingOwnerResponse {
+            owner: Some(deps.api.addr_humanize(&pending_owner.owner)?.to_string()),
+            expiry: Some(pending_owner.expiry),
+        },
+        None => PendingOwnerResponse {
+            owner: None,
+            expiry: None,
+        },
+    };
+
+    Ok(resp)
+}
+
+pub fn query_roles(deps: Deps, _env: Env, address: String) -> StdResult<RolesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let roles = load_roles(
+        deps.storage,
+        &config,
+        &deps.api.addr_canonicalize(&address)?,
+    )?;
+    let resp = RolesResponse { address, roles };
+
+    Ok(resp)
+}
+
+pub fn query_pending_roots(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<PendingRootsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|stage| Bound::exclusive(U32Key::from(stage)));
+
+    let pending_roots = PENDING_ROOTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| match item {
+            Ok((_, pending_root)) => pending_root.activation_height > env.block.height,
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|item| {
+            let (key, pending_root) = item?;
+            Ok(PendingRootResponse {
+                stage: stage_from_key(&key)?,
+                merkle_root: hex::encode(pending_root.merkle_root),
+                activation_height: pending_root.activation_height,
+            })
+        })
+        .collect::<StdResult<Vec<PendingRootResponse>>>()?;
+
+    Ok(PendingRootsResponse { pending_roots })
+}
+
+pub fn query_merkle_root(deps: Deps, env: Env, stage: u32) -> StdResult<MerkleRootResponse> {
+    let merkle_root = active_merkle_root(deps.storage, &env.block, stage)?
+        .ok_or_else(|| StdError::not_found("merkle root"))?;
+    let resp = MerkleRootResponse {
+        stage,
+        merkle_root: hex::encode(merkle_root),
+    };
+
+    Ok(resp)
+}
+
+pub fn query_stage_info(deps: Deps, env: Env, stage: u32) -> StdResult<StageInfoResponse> {
+    if !stage_exists(deps.storage, stage)? {
+        return Err(StdError::not_found("stage"));
+    }
+
+    let resp = StageInfoResponse {
+        stage,
+        merkle_root: active_merkle_root(deps.storage, &env.block, stage)?.map(hex::encode),
+        start: STAGE_START.may_load(deps.storage, U32Key::from(stage))?,
+        expiration: STAGE_EXPIRATION
+            .may_load(deps.storage, U32Key::from(stage))?
+            .unwrap_or_default(),
+        asset_infos: load_stage_assets(deps.storage, &CONFIG.load(deps.storage)?, stage)?
+            .iter()
+            .map(|asset_info| asset_info.to_normal(deps.api))
+            .collect::<StdResult<Vec<AssetInfo>>>()?,
+        frozen: is_stage_frozen(deps.storage, stage)?,
+        vesting: STAGE_VESTING.may_load(deps.storage, U32Key::from(stage))?,
+    };
+
+    Ok(resp)
+}
+
+pub fn query_stage_stats(deps: Deps, _env: Env, stage: u32) -> StdResult<StageStatsResponse> {
+    if !stage_exists(deps.storage, stage)? {
+        return Err(StdError::not_found("stage"));
+    }
+
+    let asset_infos = load_stage_assets(deps.storage, &CONFIG.load(deps.storage)?, stage)?;
+    let total_amounts = STAGE_AMOUNT.may_load(deps.storage, U32Key::from(stage))?;
+    let claimed_amounts =
+        load_stage_amounts(deps.storage, STAGE_AMOUNT_CLAIMED, stage, asset_infos.len())?;
+    // claims of stages without vesting are released in full
+    let released_amounts = if STAGE_VESTING.has(deps.storage, U32Key::from(stage)) {
+        load_stage_amounts(
+            deps.storage,
+            STAGE_AMOUNT_RELEASED,
+            stage,
+            asset_infos.len(),
+        )?
+    } else {
+        claimed_amounts.clone()
+    };
+    let withdrawn_amounts = load_stage_amounts(
+        deps.storage,
+        STAGE_AMOUNT_WITHDRAWN,
+        stage,
+        asset_infos.len(),
+    )?;
+
+    let mut assets: Vec<StageAssetStats> = vec![];
+    for (index, asset_info) in asset_infos.iter().enumerate() {
+        let total_amount = total_amounts
+            .as_ref()
+            .map(|total_amounts| total_amounts[index]);
+        let claimed_amount = claimed_amounts[index];
+        let withdrawn_amount = withdrawn_amounts[index];
+
+        assets.push(StageAssetStats {
+            asset_info: asset_info.to_normal(deps.api)?,
+            total_amount,
+            claimed_amount,
+            released_amount: released_amounts[index],
+            withdrawn_amount,
+            remaining_amount: total_amount.map(|total_amount| {
+                total_amount
+                    .saturating_sub(claimed_amount)
+                    .saturating_sub(withdrawn_amount)
+            }),
+        });
+    }
+
+    let resp = StageStatsResponse {
+        stage,
+        assets,
+        claimant_count: STAGE_CLAIMANT_COUNT
+            .may_load(deps.storage, U32Key::from(stage))?
+            .unwrap_or_default(),
+    };
 
     Ok(resp)
 }
@@ -242,15 +1630,186 @@ pub fn query_latest_stage(deps: Deps, _env: Env) -> StdResult<LatestStageRespons
 pub fn query_is_claimed(
     deps: Deps,
     _env: Env,
-    stage: u8,
+    stage: u32,
     address: String,
 ) -> StdResult<IsClaimedResponse> {
     let user_raw = deps.api.addr_canonicalize(&address)?;
-    let resp = IsClaimedResponse {
-        is_claimed: CLAIM_INDEX
-            .may_load(deps.storage, (user_raw.as_slice(), &[stage]))?
-            .unwrap_or(false),
+    let resp = match read_claim_index(deps.storage, user_raw.as_slice(), stage)? {
+        None => IsClaimedResponse {
+            is_claimed: false,
+            claim: None,
+        },
+        Some(ClaimEntry::Legacy) => IsClaimedResponse {
+            is_claimed: true,
+            claim: None,
+        },
+        Some(ClaimEntry::Recorded(record)) => IsClaimedResponse {
+            is_claimed: true,
+            claim: Some(claim_record_response(deps.api, record)?),
+        },
+    };
+
+    Ok(resp)
+}
+
+pub fn query_vesting_status(
+    deps: Deps,
+    env: Env,
+    stage: u32,
+    address: String,
+) -> StdResult<VestingStatusResponse> {
+    let vesting = STAGE_VESTING.load(deps.storage, U32Key::from(stage))?;
+    let user_raw = deps.api.addr_canonicalize(&address)?;
+    let record = match read_claim_index(deps.storage, user_raw.as_slice(), stage)? {
+        Some(ClaimEntry::Recorded(record)) => record,
+        _ => return Err(StdError::not_found("claim")),
+    };
+
+    let vested_amounts: Vec<Uint128> = record
+        .amounts
+        .iter()
+        .map(|amount| vesting.vested_amount(*amount, env.block.time))
+        .collect();
+    let released_amounts = VESTING_RELEASED
+        .may_load(deps.storage, (U32Key::from(stage), user_raw.as_slice()))?
+        .unwrap_or_else(|| vec![Uint128::zero(); record.amounts.len()]);
+    let claimable_amounts = vested_amounts
+        .iter()
+        .zip(&released_amounts)
+        .map(|(vested_amount, released_amount)| vested_amount.saturating_sub(*released_amount))
+        .collect();
+
+    Ok(VestingStatusResponse {
+        stage,
+        address,
+        vesting,
+        amounts: record.amounts,
+        vested_amounts,
+        released_amounts,
+        claimable_amounts,
+    })
+}
+
+fn claim_record_response(api: &dyn Api, record: ClaimRecord) -> StdResult<ClaimRecordResponse> {
+    Ok(ClaimRecordResponse {
+        amounts: record.amounts,
+        height: record.height,
+        time: record.time,
+        recipient: api.addr_humanize(&record.recipient)?.to_string(),
+    })
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+pub fn query_all_stages(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<AllStagesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.unwrap_or_default().saturating_add(1);
+
+    // stages are numbered contiguously, some without a root yet
+    let stages = (start..=LATEST_STAGE.load(deps.storage)?)
+        .take(limit)
+        .map(|stage| {
+            Ok(StageResponse {
+                info: query_stage_info(deps, env.clone(), stage)?,
+                stats: query_stage_stats(deps, env.clone(), stage)?,
+            })
+        })
+        .collect::<StdResult<Vec<StageResponse>>>()?;
+
+    Ok(AllStagesResponse { stages })
+}
+
+pub fn query_claimants(
+    deps: Deps,
+    _env: Env,
+    stage: u32,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ClaimantsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|address| deps.api.addr_canonicalize(&address))
+        .transpose()?
+        .map(|user_raw| Bound::exclusive(user_raw.to_vec()));
+
+    let claimants = STAGE_CLAIMANTS
+        .prefix(U32Key::from(stage))
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|user_raw| {
+            let claim = match read_claim_index(deps.storage, &user_raw, stage)? {
+                Some(ClaimEntry::Recorded(record)) => {
+                    Some(claim_record_response(deps.api, record)?)
+                }
+                _ => None,
+            };
+            Ok(ClaimantResponse {
+                address: deps
+                    .api
+                    .addr_humanize(&CanonicalAddr::from(user_raw))?
+                    .to_string(),
+                claim,
+            })
+        })
+        .collect::<StdResult<Vec<ClaimantResponse>>>()?;
+
+    Ok(ClaimantsResponse { claimants })
+}
+
+pub fn query_claim_status(
+    deps: Deps,
+    _env: Env,
+    address: String,
+    start_after: Option<u32>,
+    limit: Option<u32>,
+) -> StdResult<ClaimStatusResponse> {
+    let user_raw = deps.api.addr_canonicalize(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|stage| Bound::exclusive(U32Key::from(stage)));
+
+    let first = start_after.unwrap_or_default().saturating_add(1);
+    let stages: Vec<u32> = (first..=LATEST_STAGE.load(deps.storage)?)
+        .take(limit)
+        .collect();
+    let end = stages
+        .last()
+        .map(|stage| Bound::inclusive(U32Key::from(*stage)));
+
+    // stages claimed by the user within the page, claim index
+    // entries are only ever written for claimed stages
+    let claimed_stages: Vec<Vec<u8>> = CLAIM_INDEX
+        .prefix(user_raw.as_slice())
+        .keys(deps.storage, start, end, Order::Ascending)
+        .collect();
+
+    let resp = ClaimStatusResponse {
+        claims: stages
+            .into_iter()
+            .map(|stage| StageClaimStatus {
+                stage,
+                is_claimed: claimed_stages.contains(&stage.to_be_bytes().to_vec()),
+            })
+            .collect(),
     };
 
     Ok(resp)
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous_version = migrate_state(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate"),
+        ("previous_version", &previous_version),
+        ("version", CONTRACT_VERSION),
+    ]))
+}
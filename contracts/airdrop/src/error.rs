@@ -0,0 +1,126 @@
+use cosmwasm_std::{StdError, Uint128};
+use cw0::Expiration;
+
+use crate::state::Scheduled;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Stage {stage} already claimed by {address}")]
+    AlreadyClaimed { stage: u32, address: String },
+
+    #[error("Verification is failed")]
+    InvalidProof {},
+
+    #[error("Stage {stage} not found")]
+    StageNotFound { stage: u32 },
+
+    #[error("Invalid merkle root: {reason}")]
+    InvalidMerkleRoot { reason: String },
+
+    #[error("Invalid proof element {index}: {reason}")]
+    InvalidHexProof { index: usize, reason: String },
+
+    #[error("Invalid expiration: already expired")]
+    InvalidExpiration {},
+
+    #[error("Stage {stage} not started ({start})")]
+    StageNotStarted { stage: u32, start: Scheduled },
+
+    #[error("Stage {stage} expired ({expiration})")]
+    StageExpired { stage: u32, expiration: Expiration },
+
+    #[error("Stage {stage} not expired ({expiration})")]
+    StageNotExpired { stage: u32, expiration: Expiration },
+
+    #[error("Claim exceeds the remaining amount of stage {stage} ({remaining})")]
+    StageAmountExceeded { stage: u32, remaining: Uint128 },
+
+    #[error("Insufficient contract balance: {available} available, {required} required")]
+    InsufficientFunds {
+        required: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Stage {stage} has no total amount")]
+    StageAmountNotSet { stage: u32 },
+
+    #[error("Stage {stage} has nothing left to withdraw")]
+    NothingToWithdraw { stage: u32 },
+
+    #[error("Invalid stage assets: {reason}")]
+    InvalidAssets { reason: String },
+
+    #[error("Invalid claim amounts: {reason}")]
+    InvalidAmounts { reason: String },
+
+    #[error("Stage {stage} pays {expected} assets, got {got} amounts")]
+    AssetCountMismatch {
+        stage: u32,
+        expected: usize,
+        got: usize,
+    },
+
+    #[error("Public key does not match {address}")]
+    PubKeyMismatch { address: String },
+
+    #[error("Invalid claim signature")]
+    InvalidSignature {},
+
+    #[error("No claim given")]
+    NoClaims {},
+
+    #[error("No ownership proposal")]
+    NoOwnershipProposal {},
+
+    #[error("Ownership proposal expired ({expiry})")]
+    OwnershipProposalExpired { expiry: Expiration },
+
+    #[error("Stage {stage} has no active merkle root")]
+    StageNotActive { stage: u32 },
+
+    #[error("Stage {stage} already has a pending root")]
+    PendingRootExists { stage: u32 },
+
+    #[error("Stage {stage} has no pending root")]
+    NoPendingRoot { stage: u32 },
+
+    #[error("Invalid activation height: already reached")]
+    InvalidActivationHeight {},
+
+    #[error("Invalid vesting: {reason}")]
+    InvalidVesting { reason: String },
+
+    #[error("Stage {stage} does not vest")]
+    NoVesting { stage: u32 },
+
+    #[error("Stage {stage} not claimed by {address}")]
+    NotClaimed { stage: u32, address: String },
+
+    #[error("Nothing vested to claim from stage {stage}")]
+    NothingVested { stage: u32 },
+
+    #[error("Stage {stage} is frozen")]
+    StageFrozen { stage: u32 },
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Stage {stage} is paused")]
+    StagePaused { stage: u32 },
+
+    #[error("Cannot migrate from {contract}")]
+    InvalidContract { contract: String },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("Cannot migrate from version {previous} to older version {current}")]
+    CannotDowngrade { previous: String, current: String },
+}
@@ -1,6 +1,11 @@
+pub mod asset;
 pub mod contract;
+mod error;
+pub mod migration;
 pub mod msg;
 pub mod state;
 
+pub use crate::error::ContractError;
+
 #[cfg(test)]
 mod testing;
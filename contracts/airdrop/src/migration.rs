@@ -0,0 +1,149 @@
+use cosmwasm_std::{Empty, Order, StdResult, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use cw2::CONTRACT;
+use cw_storage_plus::{Map, U32Key};
+
+use crate::contract::validate_merkle_root;
+use crate::error::ContractError;
+use crate::state::{MERKLE_ROOT, STAGE_CLAIMANTS, STAGE_CLAIMANT_COUNT};
+use std::collections::BTreeMap;
+
+/// Merkle roots used to be stored as the hex string submitted by the owner,
+/// keyed by the single byte of their stage
+const LEGACY_MERKLE_ROOT: Map<&[u8], String> = Map::new("merkle_root");
+
+const PREFIX_CLAIM_INDEX: &[u8] = b"claim_index";
+
+/// Contracts deployed before version tracking are treated as this version
+const LEGACY_VERSION: &str = "0.0.0";
+
+type MigrationStep = fn(&mut dyn Storage) -> Result<(), ContractError>;
+
+/// Storage migrations in ascending version order. A step runs when migrating
+/// from a version older than its own up to a version at least as new
+const MIGRATIONS: &[(&str, MigrationStep)] = &[("0.1.0", migrate_v0_1_0)];
+
+/// Runs the migration steps between the stored contract version and `version`,
+/// returning the version migrated from. Downgrades and other contracts are rejected.
+pub fn migrate_state(
+    storage: &mut dyn Storage,
+    contract: &str,
+    version: &str,
+) -> Result<String, ContractError> {
+    let previous = match CONTRACT.may_load(storage)? {
+        Some(info) if info.contract != contract => {
+            return Err(ContractError::InvalidContract {
+                contract: info.contract,
+            })
+        }
+        Some(info) => info.version,
+        None => LEGACY_VERSION.to_string(),
+    };
+
+    let previous_version = parse_version(&previous)?;
+    let current_version = parse_version(version)?;
+    if previous_version > current_version {
+        return Err(ContractError::CannotDowngrade {
+            previous,
+            current: version.to_string(),
+        });
+    }
+
+    for (step_version, step) in MIGRATIONS {
+        let step_version = parse_version(step_version)?;
+        if previous_version < step_version && step_version <= current_version {
+            step(storage)?;
+        }
+    }
+
+    Ok(previous)
+}
+
+/// Parses a `major.minor.patch` version into comparable parts
+fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let invalid = || ContractError::InvalidVersion {
+        version: version.to_string(),
+    };
+
+    let parts = version
+        .split('.')
+        .map(|part| part.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u64>, ContractError>>()?;
+    match parts[..] {
+        [major, minor, patch] => Ok((major, minor, patch)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Moves the layout of the untracked deployments to the first versioned one
+fn migrate_v0_1_0(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    migrate_merkle_roots(storage)?;
+    let claimant_counts = migrate_claim_index(storage)?;
+    store_legacy_claimant_counts(storage, claimant_counts)?;
+
+    Ok(())
+}
+
+/// Rewrites every stored merkle root from its hex string form into raw bytes
+/// keyed by the big-endian stage, failing if any stored root is not a valid hash
+pub fn migrate_merkle_roots(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let legacy_roots = LEGACY_MERKLE_ROOT
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, String)>>>()?;
+
+    for (stage, merkle_root) in legacy_roots {
+        let merkle_root = validate_merkle_root(&merkle_root)?;
+        LEGACY_MERKLE_ROOT.remove(storage, &stage);
+        MERKLE_ROOT.save(storage, U32Key::from(stage[0] as u32), &merkle_root)?;
+    }
+
+    Ok(())
+}
+
+/// Moves the claim index entries keyed by a single byte stage under
+/// the big-endian stage key, keeping their stored value, and lists their
+/// claimants by stage. Returns the number of claims moved per stage
+pub fn migrate_claim_index(storage: &mut dyn Storage) -> StdResult<BTreeMap<u32, u64>> {
+    // keys are the length prefixed user followed by the stage
+    let legacy_entries = ReadonlyPrefixedStorage::new(storage, PREFIX_CLAIM_INDEX)
+        .range(None, None, Order::Ascending)
+        .filter(|(key, _)| {
+            key.len() > 2 && key.len() == 2 + u16::from_be_bytes([key[0], key[1]]) as usize + 1
+        })
+        .collect::<Vec<(Vec<u8>, Vec<u8>)>>();
+
+    let mut claimant_counts: BTreeMap<u32, u64> = BTreeMap::new();
+    let mut claimants: Vec<(u32, Vec<u8>)> = vec![];
+    let mut claim_index = PrefixedStorage::new(storage, PREFIX_CLAIM_INDEX);
+    for (key, value) in legacy_entries {
+        let (user, stage) = key.split_at(key.len() - 1);
+        let stage = stage[0] as u32;
+        claim_index.remove(&key);
+        claim_index.set(&[user, &stage.to_be_bytes()].concat(), &value);
+
+        // `false` entries were never claimed
+        if value == b"true" {
+            *claimant_counts.entry(stage).or_default() += 1;
+            claimants.push((stage, user[2..].to_vec()));
+        }
+    }
+
+    for (stage, user) in claimants {
+        STAGE_CLAIMANTS.save(storage, (U32Key::from(stage), &user), &Empty {})?;
+    }
+
+    Ok(claimant_counts)
+}
+
+/// Seeds the claimant counts of the stages claimed before claims were counted,
+/// which also freezes those stages
+pub fn store_legacy_claimant_counts(
+    storage: &mut dyn Storage,
+    claimant_counts: BTreeMap<u32, u64>,
+) -> StdResult<()> {
+    for (stage, count) in claimant_counts {
+        STAGE_CLAIMANT_COUNT.save(storage, U32Key::from(stage), &count)?;
+    }
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
+use crate::asset::{Asset, AssetInfo};
+use crate::state::{Role, Scheduled, VestingSchedule};
+use cosmwasm_std::{Binary, Timestamp, Uint128};
+use cw0::Expiration;
+use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -12,20 +16,150 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    UpdateConfig {
-        owner: Option<String>,
+    /// Proposes a new owner, who takes over once accepting before `expiry`
+    ProposeNewOwner {
+        owner: String,
+        expiry: Option<Expiration>,
     },
+    /// Makes the proposed owner the owner, sent by the proposed owner
+    AcceptOwnership {},
+    CancelOwnershipProposal {},
+    /// Halts claims and root changes of `stage`, or of every stage
+    /// when no stage is given. Sent by a pauser
+    Pause {
+        stage: Option<u32>,
+    },
+    /// Lifts a pause set by `Pause` with the same scope
+    Unpause {
+        stage: Option<u32>,
+    },
+    /// Grants a role to the address, sent by an admin
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    /// Revokes a role from the address, sent by an admin
+    RevokeRole {
+        address: String,
+        role: Role,
+    },
+    /// Rejected once the stage is frozen
     UpdateMerkleRoot {
-        stage: u8,
+        stage: u32,
         merkle_root: String,
+        /// Queues the root until the block reaches this height
+        activation_height: Option<u64>,
+    },
+    /// Drops the root queued for the stage
+    CancelPendingRoot {
+        stage: u32,
+    },
+    /// Makes the merkle root of the stage immutable. Stages
+    /// are frozen on their first claim as well
+    FreezeStage {
+        stage: u32,
     },
     RegisterMerkleRoot {
         merkle_root: String,
+        expiration: Option<Expiration>,
+        start: Option<Scheduled>,
+        total_amount: Option<Uint128>,
+        /// Defaults to the mirror token
+        asset_info: Option<AssetInfo>,
+        /// Pays several assets at once, each with its stage budget.
+        /// Replaces asset_info and total_amount
+        assets: Option<Vec<Asset>>,
+        /// Queues the root until the block reaches this height
+        activation_height: Option<u64>,
+        /// Releases claimed allocations linearly instead of at once
+        vesting: Option<VestingSchedule>,
     },
     Claim {
-        stage: u8,
-        amount: Uint128,
+        stage: u32,
+        /// Amount of single asset stages
+        amount: Option<Uint128>,
         proof: Vec<String>,
+        /// One amount per asset of the stage, in registration order.
+        /// Given in place of amount
+        amounts: Option<Vec<Uint128>>,
+        /// Claims the allocation of another address, signed by it
+        authorization: Option<ClaimAuthorization>,
+    },
+    /// Claims on behalf of `address`, which receives the tokens
+    ClaimFor {
+        stage: u32,
+        address: String,
+        /// Amount of single asset stages
+        amount: Option<Uint128>,
+        proof: Vec<String>,
+        /// One amount per asset of the stage, in registration order.
+        /// Given in place of amount
+        amounts: Option<Vec<Uint128>>,
+    },
+    /// Sends the part of the allocation of `address` vested since its last
+    /// release to the recipient of its claim. Anyone can send it
+    ClaimVested {
+        stage: u32,
+        address: String,
+    },
+    /// Claims several stages at once, with one transfer per asset
+    ClaimMany {
+        claims: Vec<StageClaim>,
+    },
+    /// Transfers the part of an expired stage's total amount
+    /// that was never claimed
+    WithdrawUnclaimed {
+        stage: u32,
+        recipient: String,
+    },
+    Receive(Cw20ReceiveMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageClaim {
+    pub stage: u32,
+    /// Amount of single asset stages
+    pub amount: Option<Uint128>,
+    pub proof: Vec<String>,
+    /// One amount per asset of the stage, in registration order.
+    /// Given in place of amount
+    pub amounts: Option<Vec<Uint128>>,
+}
+
+/// Lets a snapshot address redirect its allocation to `recipient`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimAuthorization {
+    /// Address the leaf was built for
+    pub address: String,
+    pub recipient: String,
+    /// Compressed secp256k1 public key of `address`
+    pub pub_key: Binary,
+    /// Signature of the sha256 hash of the JSON encoded SignedClaimMsg
+    pub signature: Binary,
+}
+
+/// Claim details signed by the snapshot address to redirect its allocation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedClaimMsg {
+    pub contract: String,
+    pub stage: u32,
+    pub recipient: String,
+    /// Amounts of every stage asset, comma separated
+    pub amount: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Registers a new stage funded with the sent tokens
+    RegisterMerkleRoot {
+        merkle_root: String,
+        expiration: Option<Expiration>,
+        start: Option<Scheduled>,
+        /// Queues the root until the block reaches this height
+        activation_height: Option<u64>,
+        /// Releases claimed allocations linearly instead of at once
+        vesting: Option<VestingSchedule>,
     },
 }
 
@@ -33,33 +167,202 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
-    MerkleRoot { stage: u8 },
+    /// Owner proposed by the current owner, if any
+    PendingOwner {},
+    /// Allocation of the address in a vesting stage and how much of it was released
+    VestingStatus {
+        stage: u32,
+        address: String,
+    },
+    /// Roots queued and not yet active, ordered by stage
+    PendingRoots {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    /// Roles held by the address, every role for the owner
+    Roles {
+        address: String,
+    },
+    MerkleRoot {
+        stage: u32,
+    },
+    StageInfo {
+        stage: u32,
+    },
+    StageStats {
+        stage: u32,
+    },
     LatestStage {},
-    IsClaimed { stage: u8, address: String },
+    IsClaimed {
+        stage: u32,
+        address: String,
+    },
+    AllStages {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    /// Addresses that claimed the stage, ordered by canonical address
+    Claimants {
+        stage: u32,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Claim status of the address for every registered stage
+    ClaimStatus {
+        address: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
     pub mirror_token: String,
+    pub paused: bool,
+    pub paused_stages: Vec<u32>,
+}
+
+/// Amounts are given per asset of the stage
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingStatusResponse {
+    pub stage: u32,
+    pub address: String,
+    pub vesting: VestingSchedule,
+    pub amounts: Vec<Uint128>,
+    pub vested_amounts: Vec<Uint128>,
+    pub released_amounts: Vec<Uint128>,
+    pub claimable_amounts: Vec<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRootResponse {
+    pub stage: u32,
+    pub merkle_root: String,
+    pub activation_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRootsResponse {
+    pub pending_roots: Vec<PendingRootResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub address: String,
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub owner: Option<String>,
+    pub expiry: Option<Expiration>,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MerkleRootResponse {
-    pub stage: u8,
+    pub stage: u32,
     pub merkle_root: String,
 }
 
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageInfoResponse {
+    pub stage: u32,
+    /// None until the root the stage was registered with is activated
+    pub merkle_root: Option<String>,
+    pub start: Option<Scheduled>,
+    pub expiration: Expiration,
+    pub asset_infos: Vec<AssetInfo>,
+    pub frozen: bool,
+    pub vesting: Option<VestingSchedule>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageStatsResponse {
+    pub stage: u32,
+    /// One entry per asset of the stage
+    pub assets: Vec<StageAssetStats>,
+    pub claimant_count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageAssetStats {
+    pub asset_info: AssetInfo,
+    /// Not set for stages registered without a total amount
+    pub total_amount: Option<Uint128>,
+    pub claimed_amount: Uint128,
+    /// Part of the claimed amount sent to claimants, less than it
+    /// for vesting stages until their claims are fully vested
+    pub released_amount: Uint128,
+    pub withdrawn_amount: Uint128,
+    /// Not set for stages registered without a total amount
+    pub remaining_amount: Option<Uint128>,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllStagesResponse {
+    pub stages: Vec<StageResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageResponse {
+    pub info: StageInfoResponse,
+    /// Funding status of the stage
+    pub stats: StageStatsResponse,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LatestStageResponse {
-    pub latest_stage: u8,
+    pub latest_stage: u32,
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct IsClaimedResponse {
     pub is_claimed: bool,
+    /// Not set for claims made before claims were recorded
+    pub claim: Option<ClaimRecordResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecordResponse {
+    /// One amount per asset of the stage
+    pub amounts: Vec<Uint128>,
+    pub height: u64,
+    pub time: Timestamp,
+    pub recipient: String,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimStatusResponse {
+    pub claims: Vec<StageClaimStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StageClaimStatus {
+    pub stage: u32,
+    pub is_claimed: bool,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimantsResponse {
+    pub claimants: Vec<ClaimantResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimantResponse {
+    pub address: String,
+    /// None for claims made before claims were recorded
+    pub claim: Option<ClaimRecordResponse>,
 }
@@ -1,33 +1,306 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::CanonicalAddr;
-use cw_storage_plus::{Item, Map};
+use crate::asset::AssetInfoRaw;
+use cosmwasm_std::{
+    from_slice, BlockInfo, CanonicalAddr, Empty, StdError, StdResult, Storage, Timestamp, Uint128,
+};
+use cw0::Expiration;
+use cw_storage_plus::{Item, Map, U32Key};
+use std::convert::TryInto;
+use std::fmt;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub mirror_token: CanonicalAddr,
+    /// Halts claims and root changes of every stage
+    #[serde(default)]
+    pub paused: bool,
+    /// Stages whose claims and root updates are halted, in ascending order
+    #[serde(default)]
+    pub paused_stages: Vec<u32>,
+}
+
+/// Linear release of the allocations claimed from a stage, between `start` and
+/// `end` in seconds. Nothing is released before the cliff
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+pub struct VestingSchedule {
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+impl VestingSchedule {
+    /// Part of `amount` vested at `time`
+    pub fn vested_amount(&self, amount: Uint128, time: Timestamp) -> Uint128 {
+        let now = time.seconds();
+        if now < self.cliff {
+            Uint128::zero()
+        } else if now >= self.end {
+            amount
+        } else {
+            amount.multiply_ratio(now - self.start, self.end - self.start)
+        }
+    }
+}
+
+/// Root queued for a stage, taking effect once the block reaches the activation height
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRoot {
+    pub merkle_root: [u8; 32],
+    pub activation_height: u64,
+}
+
+/// Permissions granted to addresses besides the owner, who holds all of them
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Grants and revokes roles
+    Admin,
+    /// Registers and updates merkle roots
+    RootPublisher,
+    /// Pauses and unpauses claims
+    Pauser,
+    /// Withdraws unclaimed tokens
+    Treasurer,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::Admin,
+        Role::RootPublisher,
+        Role::Pauser,
+        Role::Treasurer,
+    ];
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+            Role::RootPublisher => write!(f, "root_publisher"),
+            Role::Pauser => write!(f, "pauser"),
+            Role::Treasurer => write!(f, "treasurer"),
+        }
+    }
+}
+
+/// Owner proposed by the current owner, pending its acceptance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner: CanonicalAddr,
+    pub expiry: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimRecord {
+    /// One amount per asset of the stage
+    pub amounts: Vec<Uint128>,
+    pub height: u64,
+    pub time: Timestamp,
+    pub recipient: CanonicalAddr,
+}
+
+/// Claim index entry. Claims made before claims were recorded
+/// only tell the stage was claimed
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClaimEntry {
+    Legacy,
+    Recorded(ClaimRecord),
+}
+
+/// Scheduled represents a point in time when an event starts.
+/// Once the block reaches it, is_triggered() returns true
+/// for every block in the future
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheduled {
+    /// AtHeight will trigger when `env.block.height` >= height
+    AtHeight(u64),
+    /// AtTime will trigger when `env.block.time` >= time
+    AtTime(Timestamp),
+}
+
+impl fmt::Display for Scheduled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scheduled::AtHeight(height) => write!(f, "scheduled height: {}", height),
+            Scheduled::AtTime(time) => write!(f, "scheduled time: {}", time),
+        }
+    }
+}
+
+impl Scheduled {
+    pub fn is_triggered(&self, block: &BlockInfo) -> bool {
+        match self {
+            Scheduled::AtHeight(height) => block.height >= *height,
+            Scheduled::AtTime(time) => block.time >= *time,
+        }
+    }
 }
 
 pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
-pub const LATEST_STAGE: Item<u8> = Item::new("\u{0}\u{c}latest_stage");
+pub const LATEST_STAGE: Item<u32> = Item::new("\u{0}\u{c}latest_stage");
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+/// Roles granted to each address, keyed by canonical address
+pub const ROLES: Map<&[u8], Vec<Role>> = Map::new("roles");
+
+/// Stages are keyed by their big-endian representation. Stages registered
+/// with an activation height have no entry until their pending root is activated
+pub const MERKLE_ROOT: Map<U32Key, [u8; 32]> = Map::new("merkle_root");
+/// Pending roots are activated lazily, so entries may already be active
+pub const PENDING_ROOTS: Map<U32Key, PendingRoot> = Map::new("pending_roots");
+/// Legacy entries hold `true` in place of a ClaimRecord, so
+/// entries must be read through read_claim_index
+pub const CLAIM_INDEX: Map<(&[u8], U32Key), ClaimRecord> = Map::new("claim_index");
 
-pub const MERKLE_ROOT: Map<&[u8], String> = Map::new("merkle_root");
-pub const CLAIM_INDEX: Map<(&[u8], &[u8]), bool> = Map::new("claim_index");
+pub const STAGE_EXPIRATION: Map<U32Key, Expiration> = Map::new("stage_expiration");
+pub const STAGE_START: Map<U32Key, Scheduled> = Map::new("stage_start");
+/// Stages without an entry pay out the mirror token. Leaves of stages
+/// paying several assets commit to one amount per asset, in this order
+pub const STAGE_ASSETS: Map<U32Key, Vec<AssetInfoRaw>> = Map::new("stage_assets");
+/// Amounts are stored per asset, following the order of STAGE_ASSETS
+pub const STAGE_AMOUNT: Map<U32Key, Vec<Uint128>> = Map::new("stage_amount");
+pub const STAGE_AMOUNT_CLAIMED: Map<U32Key, Vec<Uint128>> = Map::new("stage_amount_claimed");
+pub const STAGE_AMOUNT_WITHDRAWN: Map<U32Key, Vec<Uint128>> = Map::new("stage_amount_withdrawn");
+/// Amounts owed by the stages with a total amount and not yet paid out,
+/// summed per asset and keyed by AssetInfoRaw::as_bytes
+pub const OUTSTANDING_AMOUNT: Map<&[u8], Uint128> = Map::new("outstanding_amount");
+pub const STAGE_CLAIMANT_COUNT: Map<U32Key, u64> = Map::new("stage_claimant_count");
+/// Stages without an entry pay allocations out in full on claim
+pub const STAGE_VESTING: Map<U32Key, VestingSchedule> = Map::new("stage_vesting");
+/// Amounts sent to the claimants of vesting stages, per asset
+pub const STAGE_AMOUNT_RELEASED: Map<U32Key, Vec<Uint128>> = Map::new("stage_amount_released");
+/// Amounts sent to each claimant of a vesting stage, keyed by (stage, user)
+pub const VESTING_RELEASED: Map<(U32Key, &[u8]), Vec<Uint128>> = Map::new("vesting_released");
+/// Stages frozen explicitly. Stages with claims are frozen as well
+pub const STAGE_FROZEN: Map<U32Key, bool> = Map::new("stage_frozen");
+/// Claimants keyed by (stage, user), so the claimants of a stage can be listed.
+/// Only the keys are used, the claim itself is read from CLAIM_INDEX
+pub const STAGE_CLAIMANTS: Map<(U32Key, &[u8]), Empty> = Map::new("stage_claimants");
+
+/// Roles held by the address, every role for the owner
+pub fn load_roles(
+    storage: &dyn Storage,
+    config: &Config,
+    address: &CanonicalAddr,
+) -> StdResult<Vec<Role>> {
+    if *address == config.owner {
+        return Ok(Role::ALL.to_vec());
+    }
+
+    Ok(ROLES
+        .may_load(storage, address.as_slice())?
+        .unwrap_or_default())
+}
+
+pub fn has_role(
+    storage: &dyn Storage,
+    config: &Config,
+    address: &CanonicalAddr,
+    role: Role,
+) -> StdResult<bool> {
+    Ok(load_roles(storage, config, address)?.contains(&role))
+}
+
+/// Stages are numbered from 1 up to the latest stage
+pub fn stage_exists(storage: &dyn Storage, stage: u32) -> StdResult<bool> {
+    Ok(stage > 0 && stage <= LATEST_STAGE.load(storage)?)
+}
+
+/// Root claims of the stage are verified against: the pending root
+/// once its activation height is reached, the stored root otherwise
+pub fn active_merkle_root(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    stage: u32,
+) -> StdResult<Option<[u8; 32]>> {
+    if let Some(pending_root) = PENDING_ROOTS.may_load(storage, U32Key::from(stage))? {
+        if block.height >= pending_root.activation_height {
+            return Ok(Some(pending_root.merkle_root));
+        }
+    }
+
+    MERKLE_ROOT.may_load(storage, U32Key::from(stage))
+}
+
+/// Stores the pending root of the stage as its root once activated
+pub fn activate_pending_root(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    stage: u32,
+) -> StdResult<()> {
+    if let Some(pending_root) = PENDING_ROOTS.may_load(storage, U32Key::from(stage))? {
+        if block.height >= pending_root.activation_height {
+            MERKLE_ROOT.save(storage, U32Key::from(stage), &pending_root.merkle_root)?;
+            PENDING_ROOTS.remove(storage, U32Key::from(stage));
+        }
+    }
+
+    Ok(())
+}
+
+/// A frozen stage keeps its merkle root for good
+pub fn is_stage_frozen(storage: &dyn Storage, stage: u32) -> StdResult<bool> {
+    if STAGE_FROZEN
+        .may_load(storage, U32Key::from(stage))?
+        .unwrap_or(false)
+    {
+        return Ok(true);
+    }
+
+    Ok(STAGE_CLAIMANT_COUNT
+        .may_load(storage, U32Key::from(stage))?
+        .unwrap_or(0)
+        > 0)
+}
+
+/// Decodes a stage from its big-endian storage key
+pub fn stage_from_key(key: &[u8]) -> StdResult<u32> {
+    key.try_into()
+        .map(u32::from_be_bytes)
+        .map_err(|_| StdError::generic_err("Invalid stage key"))
+}
+
+pub fn read_claim_index(
+    storage: &dyn Storage,
+    user: &[u8],
+    stage: u32,
+) -> StdResult<Option<ClaimEntry>> {
+    match storage.get(&CLAIM_INDEX.key((user, U32Key::from(stage)))) {
+        None => Ok(None),
+        Some(value) if value == b"true" => Ok(Some(ClaimEntry::Legacy)),
+        Some(value) if value == b"false" => Ok(None),
+        Some(value) => Ok(Some(ClaimEntry::Recorded(from_slice(&value)?))),
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    use cosmwasm_std::testing::mock_dependencies;
-    use cosmwasm_std::{Api, StdResult, Storage};
-    use cosmwasm_storage::{
-        bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket,
+    use crate::contract::{migrate, query, CONTRACT_NAME, CONTRACT_VERSION};
+    use crate::error::ContractError;
+    use crate::migration::{migrate_claim_index, migrate_merkle_roots};
+    use crate::msg::{
+        ClaimantResponse, ClaimantsResponse, IsClaimedResponse, LatestStageResponse,
+        MerkleRootResponse, MigrateMsg, QueryMsg, StageInfoResponse, StageStatsResponse,
     };
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{attr, from_binary, Api, StdResult, Storage};
+    use cosmwasm_storage::{bucket, singleton, singleton_read, Bucket, ReadonlyBucket};
+    use cw2::{get_contract_version, set_contract_version};
     const KEY_CONFIG: &[u8] = b"config";
 
-    pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    /// Config as stored before claims could be paused
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct LegacyConfig {
+        pub owner: CanonicalAddr,
+        pub mirror_token: CanonicalAddr,
+    }
+
+    pub fn store_config(storage: &mut dyn Storage, config: &LegacyConfig) -> StdResult<()> {
         singleton(storage, KEY_CONFIG).save(config)
     }
     pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
@@ -39,17 +312,17 @@ mod test {
         let mut deps = mock_dependencies(&[]);
         store_config(
             &mut deps.storage,
-            &Config {
+            &LegacyConfig {
                 owner: deps.api.addr_canonicalize("owner0000").unwrap(),
                 mirror_token: deps.api.addr_canonicalize("mirror0000").unwrap(),
             },
         )
         .unwrap();
 
-        assert_eq!(
-            CONFIG.load(&deps.storage).unwrap(),
-            read_config(&deps.storage).unwrap()
-        );
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config, read_config(&deps.storage).unwrap());
+        assert!(!config.paused);
+        assert!(config.paused_stages.is_empty());
     }
 
     const KEY_LATEST_STAGE: &[u8] = b"latest_stage";
@@ -67,7 +340,7 @@ mod test {
 
         assert_eq!(
             LATEST_STAGE.load(&deps.storage).unwrap(),
-            read_latest_stage(&deps.storage).unwrap()
+            u32::from(read_latest_stage(&deps.storage).unwrap())
         );
     }
 
@@ -80,27 +353,40 @@ mod test {
         let mut merkle_root_bucket: Bucket<String> = bucket(storage, PREFIX_MERKLE_ROOT);
         merkle_root_bucket.save(&[stage], &merkle_root)
     }
-    pub fn read_merkle_root(storage: &dyn Storage, stage: u8) -> StdResult<String> {
-        let merkle_root_bucket: ReadonlyBucket<String> = bucket_read(storage, PREFIX_MERKLE_ROOT);
-        merkle_root_bucket.load(&[stage])
-    }
 
     #[test]
-    fn merkle_root_legacy_compatibility() {
+    fn merkle_root_legacy_migration() {
         let mut deps = mock_dependencies(&[]);
 
-        let merkle_root_1 = "123".to_string();
-        let merkle_root_2 = "456".to_string();
-        store_merkle_root(&mut deps.storage, 1, merkle_root_1).unwrap();
-        store_merkle_root(&mut deps.storage, 2, merkle_root_2).unwrap();
+        let merkle_root_1 =
+            "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string();
+        let merkle_root_2 =
+            "634DE21CDE1044F41D90373733B0F0FB1C1C71F9652B905CDF159E73C4CF0D37".to_string();
+        store_merkle_root(&mut deps.storage, 1, merkle_root_1.clone()).unwrap();
+        store_merkle_root(&mut deps.storage, 2, merkle_root_2.clone()).unwrap();
+
+        migrate_merkle_roots(&mut deps.storage).unwrap();
+        assert_eq!(
+            hex::encode(MERKLE_ROOT.load(&deps.storage, U32Key::from(1)).unwrap()),
+            merkle_root_1
+        );
         assert_eq!(
-            MERKLE_ROOT.load(&deps.storage, &[1]).unwrap(),
-            read_merkle_root(&deps.storage, 1).unwrap()
+            hex::encode(MERKLE_ROOT.load(&deps.storage, U32Key::from(2)).unwrap()),
+            merkle_root_2.to_lowercase()
         );
+        // one byte keys are gone
+        let legacy_bucket: ReadonlyBucket<String> =
+            ReadonlyBucket::new(&deps.storage, PREFIX_MERKLE_ROOT);
+        assert_eq!(legacy_bucket.may_load(&[1]).unwrap(), None);
 
+        // invalid legacy roots block the migration
+        let mut deps = mock_dependencies(&[]);
+        store_merkle_root(&mut deps.storage, 1, "123".to_string()).unwrap();
         assert_eq!(
-            MERKLE_ROOT.load(&deps.storage, &[2]).unwrap(),
-            read_merkle_root(&deps.storage, 2).unwrap()
+            migrate_merkle_roots(&mut deps.storage).unwrap_err(),
+            ContractError::InvalidMerkleRoot {
+                reason: "Odd number of digits".to_string()
+            }
         );
     }
 
@@ -115,7 +401,7 @@ mod test {
         claimed_index_bucket.save(&[stage], &true)
     }
 
-    pub fn read_claim_index(
+    pub fn read_legacy_claim_index(
         storage: &dyn Storage,
         user: &CanonicalAddr,
         stage: u8,
@@ -129,15 +415,208 @@ mod test {
     fn claim_index_legacy_compatibility() {
         let mut deps = mock_dependencies(&[]);
 
-        let stage = 5u8;
+        let stage = 5u32;
         let addr = CanonicalAddr::from(vec![1u8, 2u8, 3u8]);
 
-        store_claim_index(&mut deps.storage, &addr, stage).unwrap();
+        store_claim_index(&mut deps.storage, &addr, stage as u8).unwrap();
+        assert!(read_legacy_claim_index(&deps.storage, &addr, stage as u8).unwrap());
+
+        migrate_claim_index(&mut deps.storage).unwrap();
+        assert!(read_legacy_claim_index(&deps.storage, &addr, stage as u8).is_err());
+        assert_eq!(
+            read_claim_index(&deps.storage, addr.as_slice(), stage).unwrap(),
+            Some(ClaimEntry::Legacy)
+        );
+        assert_eq!(
+            read_claim_index(&deps.storage, addr.as_slice(), stage + 1).unwrap(),
+            None
+        );
+
+        let record = ClaimRecord {
+            amounts: vec![Uint128::from(1000000u128)],
+            height: 12345,
+            time: Timestamp::from_seconds(1571797419),
+            recipient: addr.clone(),
+        };
+        CLAIM_INDEX
+            .save(
+                &mut deps.storage,
+                (addr.as_slice(), U32Key::from(stage + 1)),
+                &record,
+            )
+            .unwrap();
+        assert_eq!(
+            read_claim_index(&deps.storage, addr.as_slice(), stage + 1).unwrap(),
+            Some(ClaimEntry::Recorded(record))
+        );
+
+        // entries already keyed by the wide stage are left alone
+        migrate_claim_index(&mut deps.storage).unwrap();
+        assert_eq!(
+            read_claim_index(&deps.storage, addr.as_slice(), stage).unwrap(),
+            Some(ClaimEntry::Legacy)
+        );
+        assert!(matches!(
+            read_claim_index(&deps.storage, addr.as_slice(), stage + 1).unwrap(),
+            Some(ClaimEntry::Recorded(_))
+        ));
+    }
+
+    #[test]
+    fn migrate_legacy_state() {
+        let mut deps = mock_dependencies(&[]);
+
+        let claimant = deps
+            .api
+            .addr_canonicalize("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8")
+            .unwrap();
+        store_config(
+            &mut deps.storage,
+            &LegacyConfig {
+                owner: deps.api.addr_canonicalize("owner0000").unwrap(),
+                mirror_token: deps.api.addr_canonicalize("mirror0000").unwrap(),
+            },
+        )
+        .unwrap();
+        store_latest_stage(&mut deps.storage, 2u8).unwrap();
+        store_merkle_root(
+            &mut deps.storage,
+            1,
+            "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        )
+        .unwrap();
+        store_merkle_root(
+            &mut deps.storage,
+            2,
+            "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        )
+        .unwrap();
+        store_claim_index(&mut deps.storage, &claimant, 1).unwrap();
+        store_claim_index(
+            &mut deps.storage,
+            &deps.api.addr_canonicalize("addr0000").unwrap(),
+            1,
+        )
+        .unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "migrate"),
+                attr("previous_version", "0.0.0"),
+                attr("version", CONTRACT_VERSION),
+            ]
+        );
+        let contract_version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(contract_version.contract, CONTRACT_NAME);
+        assert_eq!(contract_version.version, CONTRACT_VERSION);
+
+        // migrating again to the same version leaves the state as is
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
+        let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
+        assert_eq!(2u32, latest_stage.latest_stage);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoot { stage: 2 }).unwrap();
+        let merkle_root: MerkleRootResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+            merkle_root.merkle_root
+        );
+
+        // stages claimed before the migration are counted and frozen
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 1 }).unwrap();
+        assert!(from_binary::<StageInfoResponse>(&res).unwrap().frozen);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 2 }).unwrap();
+        assert!(!from_binary::<StageInfoResponse>(&res).unwrap().frozen);
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StageStats { stage: 1 }).unwrap();
+        assert_eq!(
+            from_binary::<StageStatsResponse>(&res)
+                .unwrap()
+                .claimant_count,
+            2
+        );
+        // claimants of legacy claims are listed without their claim details
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Claimants {
+                stage: 1,
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let mut claimants = from_binary::<ClaimantsResponse>(&res).unwrap().claimants;
+        claimants.sort_by(|a, b| a.address.cmp(&b.address));
+        assert_eq!(
+            claimants,
+            vec![
+                ClaimantResponse {
+                    address: "addr0000".to_string(),
+                    claim: None,
+                },
+                ClaimantResponse {
+                    address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                    claim: None,
+                },
+            ]
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::StageStats { stage: 2 }).unwrap();
+        assert_eq!(
+            from_binary::<StageStatsResponse>(&res)
+                .unwrap()
+                .claimant_count,
+            0
+        );
+
+        for (stage, is_claimed) in [(1u32, true), (2u32, false)] {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::IsClaimed {
+                    stage,
+                    address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                from_binary::<IsClaimedResponse>(&res).unwrap().is_claimed,
+                is_claimed
+            );
+        }
+    }
+
+    #[test]
+    fn migrate_rejects_downgrade() {
+        let mut deps = mock_dependencies(&[]);
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        assert_eq!(
+            migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err(),
+            ContractError::CannotDowngrade {
+                previous: "99.0.0".to_string(),
+                current: CONTRACT_VERSION.to_string(),
+            }
+        );
+
+        set_contract_version(&mut deps.storage, "crate:cw20-base", "0.8.0").unwrap();
+        assert_eq!(
+            migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err(),
+            ContractError::InvalidContract {
+                contract: "crate:cw20-base".to_string(),
+            }
+        );
+
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "v1").unwrap();
         assert_eq!(
-            CLAIM_INDEX
-                .load(&deps.storage, (addr.as_slice(), &[stage]))
-                .unwrap(),
-            read_claim_index(&deps.storage, &addr, stage).unwrap()
+            migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err(),
+            ContractError::InvalidVersion {
+                version: "v1".to_string(),
+            }
         );
     }
 }
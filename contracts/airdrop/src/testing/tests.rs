@@ -1,11 +1,23 @@
-use crate::contract::{execute, instantiate, query};
+use crate::asset::{Asset, AssetInfo};
+use crate::contract::{execute, instantiate, query, CONTRACT_VERSION};
+use crate::error::ContractError;
 use crate::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
-    MerkleRootResponse, QueryMsg,
+    AllStagesResponse, ClaimAuthorization, ClaimRecordResponse, ClaimStatusResponse,
+    ClaimantResponse, ClaimantsResponse, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg,
+    IsClaimedResponse, LatestStageResponse, MerkleRootResponse, PendingOwnerResponse,
+    PendingRootResponse, PendingRootsResponse, QueryMsg, RolesResponse, StageAssetStats,
+    StageClaim, StageClaimStatus, StageInfoResponse, StageResponse, StageStatsResponse,
+    VestingStatusResponse,
 };
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg};
-use cw20::Cw20ExecuteMsg;
+use crate::state::{Role, Scheduled, VestingSchedule};
+use crate::testing::mock_querier::mock_dependencies;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    attr, coins, from_binary, to_binary, BankMsg, Binary, CosmosMsg, SubMsg, Uint128, WasmMsg,
+};
+use cw0::Expiration;
+use cw2::get_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
 #[test]
 fn proper_instantiate() {
@@ -29,11 +41,15 @@ fn proper_instantiate() {
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
     let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
-    assert_eq!(0u8, latest_stage.latest_stage);
+    assert_eq!(0u32, latest_stage.latest_stage);
+
+    let contract_version = get_contract_version(&deps.storage).unwrap();
+    assert_eq!(contract_version.contract, "crate:mirror-airdrop");
+    assert_eq!(contract_version.version, CONTRACT_VERSION);
 }
 
 #[test]
-fn update_config() {
+fn ownership_transfer() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
@@ -44,27 +60,355 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // only the owner proposes
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expiry: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // already expired proposals are rejected
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expiry: Some(Expiration::AtHeight(12345)),
     };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidExpiration {}) => (),
+        _ => panic!("Must return invalid expiration error"),
+    }
 
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expiry: Some(Expiration::AtHeight(12346)),
+    };
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("owner", "owner0001"),
+            attr("expiry", "expiration height: 12346"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending_owner: PendingOwnerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_owner,
+        PendingOwnerResponse {
+            owner: Some("owner0001".to_string()),
+            expiry: Some(Expiration::AtHeight(12346)),
+        }
+    );
+
+    // the owner stays until the proposal is accepted
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner0000", config.owner.as_str());
+
+    // only the proposed owner accepts
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // the proposal lapses at its expiry
+    let mut env = mock_env();
+    env.block.height = 12346;
+    let info = mock_info("owner0001", &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::AcceptOwnership {});
+    match res {
+        Err(ContractError::OwnershipProposalExpired { expiry }) => {
+            assert_eq!(expiry, Expiration::AtHeight(12346))
+        }
+        _ => panic!("Must return ownership proposal expired error"),
+    }
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "accept_ownership"),
+            attr("owner", "owner0001")
+        ]
+    );
 
-    // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0001", config.owner.as_str());
 
-    // Unauthorized err
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending_owner: PendingOwnerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_owner,
+        PendingOwnerResponse {
+            owner: None,
+            expiry: None,
+        }
+    );
+
+    // the previous owner lost its rights
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0000".to_string(),
+        expiry: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+}
+
+#[test]
+fn cancel_ownership_proposal() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelOwnershipProposal {},
+    );
+    match res {
+        Err(ContractError::NoOwnershipProposal {}) => (),
+        _ => panic!("Must return no ownership proposal error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::ProposeNewOwner {
+        owner: "owner0001".to_string(),
+        expiry: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // only the owner cancels
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelOwnershipProposal {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "cancel_ownership_proposal")]
+    );
+
+    let info = mock_info("owner0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptOwnership {},
+    );
+    match res {
+        Err(ContractError::NoOwnershipProposal {}) => (),
+        _ => panic!("Must return no ownership proposal error"),
+    }
+}
+
+#[test]
+fn roles() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the owner holds every role
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Roles {
+            address: "owner0000".to_string(),
+        },
+    )
+    .unwrap();
+    let roles: RolesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        roles.roles,
+        vec![
+            Role::Admin,
+            Role::RootPublisher,
+            Role::Pauser,
+            Role::Treasurer
+        ]
+    );
+
+    // only admins grant roles
+    let info = mock_info("publisher0000", &[]);
+    let msg = ExecuteMsg::GrantRole {
+        address: "publisher0000".to_string(),
+        role: Role::RootPublisher,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig { owner: None };
+    let msg = ExecuteMsg::GrantRole {
+        address: "admin0000".to_string(),
+        role: Role::Admin,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("admin0000", &[]);
+    let msg = ExecuteMsg::GrantRole {
+        address: "publisher0000".to_string(),
+        role: Role::RootPublisher,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "grant_role"),
+            attr("address", "publisher0000"),
+            attr("role", "root_publisher"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Roles {
+            address: "publisher0000".to_string(),
+        },
+    )
+    .unwrap();
+    let roles: RolesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        roles,
+        RolesResponse {
+            address: "publisher0000".to_string(),
+            roles: vec![Role::RootPublisher],
+        }
+    );
+
+    // root publishers register roots but cannot grant roles
+    let info = mock_info("publisher0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("publisher0000", &[]);
+    let msg = ExecuteMsg::GrantRole {
+        address: "publisher0000".to_string(),
+        role: Role::Treasurer,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // admins do not publish roots
+    let info = mock_info("admin0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 1,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("admin0000", &[]);
+    let msg = ExecuteMsg::RevokeRole {
+        address: "publisher0000".to_string(),
+        role: Role::RootPublisher,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "revoke_role"),
+            attr("address", "publisher0000"),
+            attr("role", "root_publisher"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Roles {
+            address: "publisher0000".to_string(),
+        },
+    )
+    .unwrap();
+    let roles: RolesResponse = from_binary(&res).unwrap();
+    assert!(roles.roles.is_empty());
 
+    let info = mock_info("publisher0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 1,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: None,
+    };
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        Err(ContractError::Unauthorized {}) => (),
         _ => panic!("Must return unauthorized error"),
     }
 }
@@ -85,6 +429,13 @@ fn register_merkle_root() {
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -102,7 +453,7 @@ fn register_merkle_root() {
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
     let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
-    assert_eq!(1u8, latest_stage.latest_stage);
+    assert_eq!(1u32, latest_stage.latest_stage);
 
     let res = query(
         deps.as_ref(),
@@ -117,6 +468,31 @@ fn register_merkle_root() {
         "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
         merkle_root.merkle_root
     );
+
+    // truncated merkle root
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidMerkleRoot {
+            reason: "expected 64 hex characters, got 62".to_string()
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
+    let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
+    assert_eq!(1u32, latest_stage.latest_stage);
 }
 
 #[test]
@@ -135,6 +511,13 @@ fn update_merkle_root() {
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -150,11 +533,12 @@ fn update_merkle_root() {
         ]
     );
 
-    // register new merkle root
+    // update merkle root, given in upper case
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::UpdateMerkleRoot {
         stage: 1,
-        merkle_root: "12345678".to_string(),
+        merkle_root: "85E33930E7A8F015316CB4A53A4C45D26A69F299FC4C83F17357E1FD62E8FD95".to_string(),
+        activation_height: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -163,13 +547,16 @@ fn update_merkle_root() {
         vec![
             attr("action", "update_merkle_root"),
             attr("stage", "1"),
-            attr("merkle_root", "12345678")
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            )
         ]
     );
 
     let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
     let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
-    assert_eq!(1u8, latest_stage.latest_stage);
+    assert_eq!(1u32, latest_stage.latest_stage);
 
     let res = query(
         deps.as_ref(),
@@ -180,7 +567,54 @@ fn update_merkle_root() {
     )
     .unwrap();
     let merkle_root: MerkleRootResponse = from_binary(&res).unwrap();
-    assert_eq!("12345678".to_string(), merkle_root.merkle_root);
+    assert_eq!(
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        merkle_root.merkle_root
+    );
+
+    // Invalid merkle root
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 1,
+        merkle_root: "12345678".to_string(),
+        activation_height: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidMerkleRoot {
+            reason: "expected 64 hex characters, got 8".to_string()
+        }
+    );
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 1,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    // Stage not registered
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 2,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::StageNotFound { stage }) => assert_eq!(stage, 2u32),
+        _ => panic!("Must return stage not found error"),
+    }
 }
 
 #[test]
@@ -199,24 +633,40 @@ fn claim() {
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let info = mock_info("owner0000", &[]);
     let msg = ExecuteMsg::RegisterMerkleRoot {
         merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
     };
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let msg = ExecuteMsg::Claim {
-        amount: Uint128::from(1000001u128),
-        stage: 1u8,
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
         proof: vec![
             "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
             "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
             "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
             "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
         ],
+        amounts: None,
+        authorization: None,
     };
 
     let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
@@ -262,20 +712,25 @@ fn claim() {
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "already claimed"),
+        Err(ContractError::AlreadyClaimed { stage, address }) => {
+            assert_eq!(stage, 1u32);
+            assert_eq!(address, "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8");
+        }
         _ => panic!("DO NOT ENTER HERE"),
     }
 
     // Claim next airdrop
     let msg = ExecuteMsg::Claim {
-        amount: Uint128::from(2000001u128),
-        stage: 2u8,
+        amount: Some(Uint128::from(2000001u128)),
+        stage: 2u32,
         proof: vec![
             "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
             "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
             "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
             "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
         ],
+        amounts: None,
+        authorization: None,
     };
 
     let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
@@ -302,4 +757,3011 @@ fn claim() {
             attr("amount", "2000001")
         ]
     );
+
+    // Proof for another amount
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000002u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidProof {}) => (),
+        _ => panic!("Must return invalid proof error"),
+    }
+
+    // Stage not registered
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 3u32,
+        proof: vec![],
+        amounts: None,
+        authorization: None,
+    };
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::StageNotFound { stage }) => assert_eq!(stage, 3u32),
+        _ => panic!("Must return stage not found error"),
+    }
+}
+
+#[test]
+fn stage_beyond_u8() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Register stages 1 to 255 with the stage 1 root
+    for _ in 0..255 {
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+                .to_string(),
+            expiration: None,
+            start: None,
+            total_amount: None,
+            asset_info: None,
+            assets: None,
+            activation_height: None,
+            vesting: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // Stage 256 no longer wraps around
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "256"),
+            attr(
+                "merkle_root",
+                "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
+            ),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::LatestStage {}).unwrap();
+    let latest_stage: LatestStageResponse = from_binary(&res).unwrap();
+    assert_eq!(256u32, latest_stage.latest_stage);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoot { stage: 1 }).unwrap();
+    let merkle_root: MerkleRootResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        merkle_root.merkle_root
+    );
+
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(2000001u128)),
+        stage: 256u32,
+        proof: vec![
+            "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+            "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+            "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+            "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("stage", "256"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("amount", "2000001")
+        ]
+    );
+
+    // Claiming stage 256 leaves stage 0 untouched
+    for (stage, is_claimed) in [(0u32, false), (256u32, true)] {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsClaimed {
+                stage,
+                address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            from_binary::<IsClaimedResponse>(&res).unwrap().is_claimed,
+            is_claimed
+        );
+    }
+}
+
+#[test]
+fn pause() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for merkle_root in [
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95",
+        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37",
+    ] {
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: merkle_root.to_string(),
+            expiration: None,
+            start: None,
+            total_amount: None,
+            asset_info: None,
+            assets: None,
+            activation_height: None,
+            vesting: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // only pausers pause
+    let info = mock_info("pauser0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Pause { stage: Some(1) },
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::GrantRole {
+        address: "pauser0000".to_string(),
+        role: Role::Pauser,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("pauser0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Pause { stage: Some(3) },
+    );
+    match res {
+        Err(ContractError::StageNotFound { stage }) => assert_eq!(stage, 3u32),
+        _ => panic!("Must return stage not found error"),
+    }
+
+    let info = mock_info("pauser0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Pause { stage: Some(1) },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "pause"), attr("stage", "1")]
+    );
+
+    // claims and root updates of the paused stage are rejected
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(1000001u128)),
+            stage: 1u32,
+            proof: vec![
+                "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+                "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+                "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+                "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::StagePaused { stage: 1 });
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 1,
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        activation_height: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::StagePaused { stage: 1 });
+
+    // pausing every stage
+    let info = mock_info("pauser0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Pause { stage: None },
+    )
+    .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "pause")]);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert!(config.paused);
+    assert_eq!(config.paused_stages, vec![1u32]);
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(2000001u128)),
+            stage: 2u32,
+            proof: vec![
+                "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+                "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+                "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+                "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+
+    // lifting the global pause keeps the stage pause
+    let info = mock_info("pauser0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Unpause { stage: None },
+    )
+    .unwrap();
+    assert_eq!(res.attributes, vec![attr("action", "unpause")]);
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(2000001u128)),
+            stage: 2u32,
+            proof: vec![
+                "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+                "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+                "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+                "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    )
+    .unwrap();
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(1000001u128)),
+            stage: 1u32,
+            proof: vec![
+                "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+                "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+                "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+                "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::StagePaused { stage: 1 });
+
+    let info = mock_info("pauser0000", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Unpause { stage: Some(1) },
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert!(!config.paused);
+    assert!(config.paused_stages.is_empty());
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(1000001u128)),
+            stage: 1u32,
+            proof: vec![
+                "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+                "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+                "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+                "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn freeze_stage() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for merkle_root in [
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95",
+        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37",
+    ] {
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: merkle_root.to_string(),
+            expiration: None,
+            start: None,
+            total_amount: None,
+            asset_info: None,
+            assets: None,
+            activation_height: None,
+            vesting: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // only root publishers freeze stages
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FreezeStage { stage: 1 },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FreezeStage { stage: 3 },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::StageNotFound { stage: 3 });
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FreezeStage { stage: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "freeze_stage"), attr("stage", "1")]
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 1,
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        activation_height: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::StageFrozen { stage: 1 });
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 1 }).unwrap();
+    assert!(from_binary::<StageInfoResponse>(&res).unwrap().frozen);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 2 }).unwrap();
+    assert!(!from_binary::<StageInfoResponse>(&res).unwrap().frozen);
+
+    // the first claim freezes the stage
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(2000001u128)),
+        stage: 2u32,
+        proof: vec![
+            "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+            "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+            "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+            "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 2 }).unwrap();
+    assert!(from_binary::<StageInfoResponse>(&res).unwrap().frozen);
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 2,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::StageFrozen { stage: 2 });
+}
+
+#[test]
+fn pending_roots() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // activation heights already reached are rejected
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: Some(12345),
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidActivationHeight {});
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: Some(12400),
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("activation_height", "12400"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingRoots {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending_roots: PendingRootsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        pending_roots.pending_roots,
+        vec![PendingRootResponse {
+            stage: 1,
+            merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+                .to_string(),
+            activation_height: 12400,
+        }]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageInfoResponse>(&res).unwrap().merkle_root,
+        None
+    );
+
+    // claims wait for the activation
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(1000001u128)),
+            stage: 1u32,
+            proof: vec![
+                "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+                "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+                "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+                "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::StageNotActive { stage: 1 });
+
+    // freezing would drop the only root of the stage
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::FreezeStage { stage: 1 },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::StageNotActive { stage: 1 });
+
+    let mut env = mock_env();
+    env.block.height = 12400;
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::MerkleRoot { stage: 1 },
+    )
+    .unwrap();
+    let merkle_root: MerkleRootResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        merkle_root.merkle_root
+    );
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(1000001u128)),
+            stage: 1u32,
+            proof: vec![
+                "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+                "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+                "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+                "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PendingRoots {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pending_roots: PendingRootsResponse = from_binary(&res).unwrap();
+    assert!(pending_roots.pending_roots.is_empty());
+
+    // queued updates keep the current root until cancelled or activated
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 2,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: Some(12500),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_merkle_root"),
+            attr("stage", "2"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("activation_height", "12500"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MerkleRoot { stage: 2 }).unwrap();
+    let merkle_root: MerkleRootResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        merkle_root.merkle_root
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 2,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::PendingRootExists { stage: 2 }
+    );
+
+    // only root publishers cancel
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelPendingRoot { stage: 2 },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::GrantRole {
+        address: "publisher0000".to_string(),
+        role: Role::RootPublisher,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("publisher0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelPendingRoot { stage: 2 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "cancel_pending_root"), attr("stage", "2")]
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelPendingRoot { stage: 2 },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NoPendingRoot { stage: 2 });
+
+    // the first claim freezes the stage and drops the queued root
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::UpdateMerkleRoot {
+        stage: 2,
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        activation_height: Some(12500),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Claim {
+            amount: Some(Uint128::from(2000001u128)),
+            stage: 2u32,
+            proof: vec![
+                "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+                "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+                "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+                "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+            ],
+            amounts: None,
+            authorization: None,
+        },
+    )
+    .unwrap();
+
+    let mut env = mock_env();
+    env.block.height = 12500;
+    let res = query(deps.as_ref(), env, QueryMsg::MerkleRoot { stage: 2 }).unwrap();
+    let merkle_root: MerkleRootResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        merkle_root.merkle_root
+    );
+}
+
+#[test]
+fn vesting() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let now = mock_env().block.time.seconds();
+    let env_at = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: Some(VestingSchedule {
+            start: now,
+            cliff: now + 1100,
+            end: now + 1000,
+        }),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidVesting {
+            reason: "cliff must be between start and end".to_string()
+        }
+    );
+
+    let vesting = VestingSchedule {
+        start: now,
+        cliff: now + 100,
+        end: now + 1000,
+    };
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: Some(vesting),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("vesting", format!("{}-{}-{}", now, now + 100, now + 1000)),
+        ]
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageInfoResponse>(&res).unwrap().vesting,
+        Some(vesting)
+    );
+
+    // nothing is released before the cliff
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), env_at(50), info, msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("stage", "1"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("amount", "1000001"),
+            attr("released_amount", "0"),
+        ]
+    );
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(50),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NothingVested { stage: 1 });
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(500),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 1,
+            address: "addr0000".to_string(),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NotClaimed {
+            stage: 1,
+            address: "addr0000".to_string()
+        }
+    );
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(500),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 2,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NoVesting { stage: 2 });
+
+    // half of the vesting period has passed
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(500),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_vested"),
+            attr("stage", "1"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("sender", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("amount", "500000"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        env_at(600),
+        QueryMsg::VestingStatus {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<VestingStatusResponse>(&res).unwrap(),
+        VestingStatusResponse {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+            vesting,
+            amounts: vec![Uint128::from(1000001u128)],
+            vested_amounts: vec![Uint128::from(600000u128)],
+            released_amounts: vec![Uint128::from(500000u128)],
+            claimable_amounts: vec![Uint128::from(100000u128)],
+        }
+    );
+
+    // the rest is released once the vesting period ends, whoever asks for it
+    let info = mock_info("relayer0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(2000),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_vested"),
+            attr("stage", "1"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("sender", "relayer0000"),
+            attr("amount", "500001"),
+        ]
+    );
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(3000),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NothingVested { stage: 1 });
+
+    // redirected claims keep paying their recipient
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "417f464f3f0912d82aee5c565c17a7e17931ba5195977c69bbc2327c0132a45e".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: Some(vesting),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("relayer0000", &[]);
+    let msg = ExecuteMsg::Claim {
+        stage: 3u32,
+        amount: Some(Uint128::from(1000000u128)),
+        proof: vec!["09d13ae144b98361d9454d61d128adccef089822e51a54e11378dcc339a4d178".to_string()],
+        amounts: None,
+        authorization: Some(ClaimAuthorization {
+            address: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+            recipient: "recipient0000".to_string(),
+            pub_key: Binary::from_base64("AzLKsh9BIJvlwE1PWLM0H6Q4IMdFNe4FxrhUIdHcNjh0").unwrap(),
+            signature: Binary::from_base64(
+                "WHbGSl06yK5LxAx72p3qwss+NIKVk6HSTUS1Wh/N1hVrIYDMVzJdvE4puEA2XkmLtzgr2dLysAeUFJC8pKpC9A==",
+            )
+            .unwrap(),
+        }),
+    };
+    let res = execute(deps.as_mut(), env_at(500), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "recipient0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let info = mock_info("recipient0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        env_at(2000),
+        info,
+        ExecuteMsg::ClaimVested {
+            stage: 3,
+            address: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "recipient0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_vested"),
+            attr("stage", "3"),
+            attr("address", "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j"),
+            attr("sender", "recipient0000"),
+            attr("recipient", "recipient0000"),
+            attr("amount", "500000"),
+        ]
+    );
+}
+
+#[test]
+fn vesting_with_total_amount() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(3000000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let now = mock_env().block.time.seconds();
+    let env_at = |seconds: u64, height: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env.block.height = height;
+        env
+    };
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "417f464f3f0912d82aee5c565c17a7e17931ba5195977c69bbc2327c0132a45e".to_string(),
+        expiration: Some(Expiration::AtHeight(12500)),
+        start: None,
+        total_amount: Some(Uint128::from(2000000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: Some(VestingSchedule {
+            start: now,
+            cliff: now + 100,
+            end: now + 1000,
+        }),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // claimed before the cliff, nothing is sent
+    let msg = ExecuteMsg::Claim {
+        stage: 1u32,
+        amount: Some(Uint128::from(500000u128)),
+        proof: vec!["bcfb589a14beb8bf1971b2a131e7a48ec33c5339b2a64c0d555a696f221a3d13".to_string()],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env_at(50, 12345), info, msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    // claimed halfway, half of the allocation is sent
+    let msg = ExecuteMsg::Claim {
+        stage: 1u32,
+        amount: Some(Uint128::from(1000000u128)),
+        proof: vec!["09d13ae144b98361d9454d61d128adccef089822e51a54e11378dcc339a4d178".to_string()],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j", &[]);
+    let res = execute(deps.as_mut(), env_at(500, 12345), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2500000u128))],
+    )]);
+
+    let res = query(
+        deps.as_ref(),
+        env_at(500, 12345),
+        QueryMsg::StageStats { stage: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<StageStatsResponse>(&res).unwrap().assets,
+        vec![StageAssetStats {
+            asset_info: AssetInfo::Token {
+                contract_addr: "mirror0000".to_string(),
+            },
+            total_amount: Some(Uint128::from(2000000u128)),
+            claimed_amount: Uint128::from(1500000u128),
+            released_amount: Uint128::from(500000u128),
+            withdrawn_amount: Uint128::zero(),
+            remaining_amount: Some(Uint128::from(500000u128)),
+        }]
+    );
+
+    // claimed allocations stay in the contract once the stage expires
+    let env = env_at(600, 12500);
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::WithdrawUnclaimed {
+        stage: 1,
+        recipient: "treasury0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+    )]);
+
+    let register_msg = |total_amount: u128| ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(total_amount)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), env, info, register_msg(1000001));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientFunds {
+            required: Uint128::from(1000001u128),
+            available: Uint128::from(1000000u128),
+        }
+    );
+
+    // vested tokens are still paid out after the expiration
+    let env = env_at(2000, 12600);
+    let info = mock_info("relayer0000", &[]);
+    let msg = ExecuteMsg::ClaimVested {
+        stage: 1,
+        address: "addr0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let info = mock_info("relayer0000", &[]);
+    let msg = ExecuteMsg::ClaimVested {
+        stage: 1,
+        address: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+                amount: Uint128::from(500000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+
+    // nothing is owed anymore
+    let info = mock_info("owner0000", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, register_msg(1000001));
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientFunds {
+            required: Uint128::from(1000001u128),
+            available: Uint128::from(1000000u128),
+        }
+    );
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), env, info, register_msg(1000000)).unwrap();
+}
+
+#[test]
+fn claim_malformed_hex() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let valid_proof =
+        "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string();
+    let cases = vec![
+        (
+            // odd length
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e",
+            "Odd number of digits",
+        ),
+        (
+            // non hex character
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24ez",
+            "Invalid character 'z' at position 63",
+        ),
+        (
+            // wrong length
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1ff",
+            "expected 64 hex characters, got 66",
+        ),
+    ];
+
+    for (proof, expected) in cases {
+        let msg = ExecuteMsg::Claim {
+            amount: Some(Uint128::from(1000001u128)),
+            stage: 1u32,
+            proof: vec![valid_proof.clone(), proof.to_string()],
+            amounts: None,
+            authorization: None,
+        };
+
+        let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        assert_eq!(
+            res.unwrap_err(),
+            ContractError::InvalidHexProof {
+                index: 1,
+                reason: expected.to_string(),
+            }
+        );
+    }
+}
+
+#[test]
+fn withdraw_unclaimed() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(10000000u128),
+        )],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // already expired
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: Some(Expiration::AtHeight(mock_env().block.height)),
+        start: None,
+        total_amount: Some(Uint128::from(10000000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidExpiration {});
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: Some(Expiration::AtHeight(12500)),
+        start: None,
+        total_amount: Some(Uint128::from(10000000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("expiration", "expiration height: 12500"),
+            attr("total_amount", "10000000"),
+        ]
+    );
+
+    // stage without total amount
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: Some(Expiration::AtHeight(12500)),
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // not expired yet
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::WithdrawUnclaimed {
+        stage: 1,
+        recipient: "treasury0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::StageNotExpired {
+            stage: 1,
+            expiration: Expiration::AtHeight(12500),
+        }
+    );
+
+    let claim_msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, claim_msg.clone()).unwrap();
+
+    let mut env = mock_env();
+    env.block.height = 12500;
+
+    // claims are closed once expired
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, claim_msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::StageExpired {
+            stage: 1,
+            expiration: Expiration::AtHeight(12500),
+        }
+    );
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::WithdrawUnclaimed {
+        stage: 1,
+        recipient: "treasury0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::WithdrawUnclaimed {
+        stage: 1,
+        recipient: "treasury0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury0000".to_string(),
+                amount: Uint128::from(8999999u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "withdraw_unclaimed"),
+            attr("stage", "1"),
+            attr("recipient", "treasury0000"),
+            attr("amount", "8999999"),
+        ]
+    );
+
+    // already withdrawn
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NothingToWithdraw { stage: 1 }
+    );
+
+    let msg = ExecuteMsg::WithdrawUnclaimed {
+        stage: 2,
+        recipient: "treasury0000".to_string(),
+    };
+    let res = execute(deps.as_mut(), env, info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::StageAmountNotSet { stage: 2 }
+    );
+}
+
+#[test]
+fn stage_start() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let start = Scheduled::AtTime(mock_env().block.time.plus_seconds(100));
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: Some(Expiration::AtHeight(12500)),
+        start: Some(start),
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("expiration", "expiration height: 12500"),
+            attr("start", "scheduled time: 1571797519.879305533"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageInfoResponse>(&res).unwrap(),
+        StageInfoResponse {
+            stage: 1,
+            merkle_root: Some(
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string()
+            ),
+            start: Some(start),
+            expiration: Expiration::AtHeight(12500),
+            asset_infos: vec![AssetInfo::Token {
+                contract_addr: "mirror0000".to_string(),
+            }],
+            frozen: false,
+            vesting: None,
+        }
+    );
+
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::StageNotStarted { stage: 1, start }
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // stages without start or expiration
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 2 }).unwrap();
+    assert_eq!(
+        from_binary::<StageInfoResponse>(&res).unwrap(),
+        StageInfoResponse {
+            stage: 2,
+            merkle_root: Some(
+                "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string()
+            ),
+            start: None,
+            expiration: Expiration::Never {},
+            asset_infos: vec![AssetInfo::Token {
+                contract_addr: "mirror0000".to_string(),
+            }],
+            frozen: false,
+            vesting: None,
+        }
+    );
+}
+
+#[test]
+fn stage_stats() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(3500000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1500000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(2000000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageStats { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageStatsResponse>(&res).unwrap(),
+        StageStatsResponse {
+            stage: 1,
+            assets: vec![StageAssetStats {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "mirror0000".to_string(),
+                },
+                total_amount: Some(Uint128::from(1500000u128)),
+                claimed_amount: Uint128::from(1000001u128),
+                released_amount: Uint128::from(1000001u128),
+                withdrawn_amount: Uint128::zero(),
+                remaining_amount: Some(Uint128::from(499999u128)),
+            }],
+            claimant_count: 1,
+        }
+    );
+
+    // claim larger than the stage budget
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(2000001u128)),
+        stage: 2u32,
+        proof: vec![
+            "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+            "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+            "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+            "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::StageAmountExceeded {
+            stage: 2,
+            remaining: Uint128::from(2000000u128),
+        }
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageStats { stage: 2 }).unwrap();
+    assert_eq!(
+        from_binary::<StageStatsResponse>(&res).unwrap(),
+        StageStatsResponse {
+            stage: 2,
+            assets: vec![StageAssetStats {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "mirror0000".to_string(),
+                },
+                total_amount: Some(Uint128::from(2000000u128)),
+                claimed_amount: Uint128::zero(),
+                released_amount: Uint128::zero(),
+                withdrawn_amount: Uint128::zero(),
+                remaining_amount: Some(Uint128::from(2000000u128)),
+            }],
+            claimant_count: 0,
+        }
+    );
+}
+
+#[test]
+fn register_merkle_root_insufficient_funds() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(3000000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: Some(Expiration::AtHeight(12500)),
+        start: None,
+        total_amount: Some(Uint128::from(2000000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 1000000 left after the first stage obligations
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1500000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientFunds {
+            required: Uint128::from(1500000u128),
+            available: Uint128::from(1000000u128),
+        }
+    );
+
+    // claimed tokens left the contract
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1999999u128))],
+    )]);
+
+    // expired stages keep their unclaimed amount until withdrawn
+    let mut env = mock_env();
+    env.block.height = 12500;
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1000001u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientFunds {
+            required: Uint128::from(1000001u128),
+            available: Uint128::from(1000000u128),
+        }
+    );
+
+    let msg_withdraw = ExecuteMsg::WithdrawUnclaimed {
+        stage: 1,
+        recipient: "treasury0000".to_string(),
+    };
+    let info = mock_info("owner0000", &[]);
+    let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg_withdraw).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientFunds {
+            required: Uint128::from(1000001u128),
+            available: Uint128::from(1000000u128),
+        }
+    );
+
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1000000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn register_merkle_root_by_sending_tokens() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "owner0000".to_string(),
+        amount: Uint128::from(5000000u128),
+        msg: to_binary(&Cw20HookMsg::RegisterMerkleRoot {
+            merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+                .to_string(),
+            expiration: Some(Expiration::AtHeight(12500)),
+            start: None,
+            activation_height: None,
+            vesting: None,
+        })
+        .unwrap(),
+    });
+
+    // only the mirror token can fund stages
+    let info = mock_info("token0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("mirror0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("expiration", "expiration height: 12500"),
+            attr("total_amount", "5000000"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageStats { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageStatsResponse>(&res).unwrap(),
+        StageStatsResponse {
+            stage: 1,
+            assets: vec![StageAssetStats {
+                asset_info: AssetInfo::Token {
+                    contract_addr: "mirror0000".to_string(),
+                },
+                total_amount: Some(Uint128::from(5000000u128)),
+                claimed_amount: Uint128::zero(),
+                released_amount: Uint128::zero(),
+                withdrawn_amount: Uint128::zero(),
+                remaining_amount: Some(Uint128::from(5000000u128)),
+            }],
+            claimant_count: 0,
+        }
+    );
+
+    // only the owner can register stages
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(5000000u128),
+        msg: to_binary(&Cw20HookMsg::RegisterMerkleRoot {
+            merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
+                .to_string(),
+            expiration: None,
+            start: None,
+            activation_height: None,
+            vesting: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("mirror0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
+
+#[test]
+fn claim_native_token() {
+    let mut deps = mock_dependencies(&coins(1500000u128, "uusd"));
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1500000u128)),
+        asset_info: Some(AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        }),
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+            ),
+            attr("total_amount", "1500000"),
+            attr("asset_info", "uusd"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageInfo { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageInfoResponse>(&res).unwrap().asset_infos,
+        vec![AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        }]
+    );
+
+    // the whole uusd balance is owed to the first stage
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1u128)),
+        asset_info: Some(AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        }),
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientFunds {
+            required: Uint128::from(1u128),
+            available: Uint128::zero(),
+        }
+    );
+
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(1000001u128)),
+        stage: 1u32,
+        proof: vec![
+            "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+            "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+            "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+            "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+            amount: coins(1000001u128, "uusd"),
+        }))]
+    );
+}
+
+#[test]
+fn claim_multiple_assets() {
+    let mut deps = mock_dependencies(&coins(1000000u128, "uusd"));
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let assets = vec![
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "mirror0000".to_string(),
+            },
+            amount: Uint128::from(1500000u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+    ];
+
+    // assets replace total_amount and asset_info
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85dcb94ae85fc4df0628da4b6b92b1115855ffc6b95bf29831c1119f2c638c99".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1500000u128)),
+        asset_info: None,
+        assets: Some(assets.clone()),
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidAssets {
+            reason: "cannot be combined with asset_info or total_amount".to_string(),
+        }
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85dcb94ae85fc4df0628da4b6b92b1115855ffc6b95bf29831c1119f2c638c99".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: Some(vec![assets[1].clone(), assets[1].clone()]),
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidAssets {
+            reason: "duplicate asset uusd".to_string(),
+        }
+    );
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85dcb94ae85fc4df0628da4b6b92b1115855ffc6b95bf29831c1119f2c638c99".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: Some(assets),
+        activation_height: None,
+        vesting: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "register_merkle_root"),
+            attr("stage", "1"),
+            attr(
+                "merkle_root",
+                "85dcb94ae85fc4df0628da4b6b92b1115855ffc6b95bf29831c1119f2c638c99"
+            ),
+            attr("total_amount", "1500000,1000000"),
+            attr("asset_info", "mirror0000,uusd"),
+        ]
+    );
+
+    // the leaf commits to one amount per asset
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(500000u128)),
+        stage: 1u32,
+        proof: vec!["374922da6f327d91c4aa9c5adad3ec90d90cb08ee6bfd591ea0648abe109130b".to_string()],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::AssetCountMismatch {
+            stage: 1,
+            expected: 2,
+            got: 1,
+        }
+    );
+
+    // amount and amounts are exclusive, and one of them is required
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(500000u128)),
+        stage: 1u32,
+        proof: vec!["374922da6f327d91c4aa9c5adad3ec90d90cb08ee6bfd591ea0648abe109130b".to_string()],
+        amounts: Some(vec![Uint128::from(500000u128), Uint128::from(1000000u128)]),
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidAmounts {
+            reason: "amount cannot be combined with amounts".to_string(),
+        }
+    );
+
+    let msg = ExecuteMsg::Claim {
+        amount: None,
+        stage: 1u32,
+        proof: vec!["374922da6f327d91c4aa9c5adad3ec90d90cb08ee6bfd591ea0648abe109130b".to_string()],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidAmounts {
+            reason: "no amount given".to_string(),
+        }
+    );
+
+    let msg = ExecuteMsg::Claim {
+        amount: None,
+        stage: 1u32,
+        proof: vec!["374922da6f327d91c4aa9c5adad3ec90d90cb08ee6bfd591ea0648abe109130b".to_string()],
+        amounts: Some(vec![Uint128::from(500000u128), Uint128::from(1000000u128)]),
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "mirror0000".to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                    amount: Uint128::from(500000u128),
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                amount: coins(1000000u128, "uusd"),
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("stage", "1"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("amount", "500000,1000000"),
+        ]
+    );
+
+    // zero amounts are not transferred
+    let msg = ExecuteMsg::Claim {
+        amount: None,
+        stage: 1u32,
+        proof: vec!["4bf16f3e82304ea25ec7cbac90cd55664e20d81dc4cecbfbadfe4a2e3a456639".to_string()],
+        amounts: Some(vec![Uint128::from(1000000u128), Uint128::zero()]),
+        authorization: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StageStats { stage: 1 }).unwrap();
+    assert_eq!(
+        from_binary::<StageStatsResponse>(&res).unwrap(),
+        StageStatsResponse {
+            stage: 1,
+            assets: vec![
+                StageAssetStats {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: "mirror0000".to_string(),
+                    },
+                    total_amount: Some(Uint128::from(1500000u128)),
+                    claimed_amount: Uint128::from(1500000u128),
+                    released_amount: Uint128::from(1500000u128),
+                    withdrawn_amount: Uint128::zero(),
+                    remaining_amount: Some(Uint128::zero()),
+                },
+                StageAssetStats {
+                    asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    total_amount: Some(Uint128::from(1000000u128)),
+                    claimed_amount: Uint128::from(1000000u128),
+                    released_amount: Uint128::from(1000000u128),
+                    withdrawn_amount: Uint128::zero(),
+                    remaining_amount: Some(Uint128::zero()),
+                },
+            ],
+            claimant_count: 2,
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsClaimed {
+            stage: 1,
+            address: "addr0000".to_string(),
+        },
+    )
+    .unwrap();
+    assert!(from_binary::<IsClaimedResponse>(&res).unwrap().is_claimed);
+}
+
+#[test]
+fn claim_for() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let proof = vec![
+        "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+        "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+        "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+        "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+    ];
+
+    // the proof is verified against the given address, not the sender
+    let msg = ExecuteMsg::ClaimFor {
+        stage: 1u32,
+        address: "relayer0000".to_string(),
+        amount: Some(Uint128::from(1000001u128)),
+        proof: proof.clone(),
+        amounts: None,
+    };
+    let info = mock_info("relayer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidProof {});
+
+    let msg = ExecuteMsg::ClaimFor {
+        stage: 1u32,
+        address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        amount: Some(Uint128::from(1000001u128)),
+        proof: proof.clone(),
+        amounts: None,
+    };
+    let info = mock_info("relayer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                amount: Uint128::from(1000001u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_for"),
+            attr("stage", "1"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("sender", "relayer0000"),
+            attr("amount", "1000001"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsClaimed {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        },
+    )
+    .unwrap();
+    assert!(from_binary::<IsClaimedResponse>(&res).unwrap().is_claimed);
+
+    // the beneficiary can no longer claim by itself
+    let msg = ExecuteMsg::Claim {
+        stage: 1u32,
+        amount: Some(Uint128::from(1000001u128)),
+        proof,
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::AlreadyClaimed {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        }
+    );
+}
+
+#[test]
+fn claim_with_authorization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "417f464f3f0912d82aee5c565c17a7e17931ba5195977c69bbc2327c0132a45e".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let authorization = ClaimAuthorization {
+        address: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+        recipient: "recipient0000".to_string(),
+        pub_key: Binary::from_base64("AzLKsh9BIJvlwE1PWLM0H6Q4IMdFNe4FxrhUIdHcNjh0").unwrap(),
+        signature: Binary::from_base64(
+            "PcEhgn1MTa7WAitpUS5u1Ehf5gajqjIsUGWaGcuwVRJFApb187y1kiVQmSXGrkU4otd7kpTl5UwIOAwB6V//5A==",
+        )
+        .unwrap(),
+    };
+    let claim_msg = |authorization: ClaimAuthorization| ExecuteMsg::Claim {
+        stage: 1u32,
+        amount: Some(Uint128::from(1000000u128)),
+        proof: vec!["09d13ae144b98361d9454d61d128adccef089822e51a54e11378dcc339a4d178".to_string()],
+        amounts: None,
+        authorization: Some(authorization),
+    };
+
+    // the public key must belong to the snapshot address
+    let info = mock_info("relayer0000", &[]);
+    let msg = claim_msg(ClaimAuthorization {
+        address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        ..authorization.clone()
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::PubKeyMismatch {
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        }
+    );
+
+    // the signature covers the recipient
+    let info = mock_info("relayer0000", &[]);
+    let msg = claim_msg(ClaimAuthorization {
+        recipient: "relayer0000".to_string(),
+        ..authorization.clone()
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidSignature {});
+
+    let info = mock_info("relayer0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, claim_msg(authorization)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "recipient0000".to_string(),
+                amount: Uint128::from(1000000u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim"),
+            attr("stage", "1"),
+            attr("address", "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j"),
+            attr("recipient", "recipient0000"),
+            attr("amount", "1000000"),
+        ]
+    );
+
+    // the leaf stays claimed by the snapshot address
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::IsClaimed {
+            stage: 1,
+            address: "terra1dfvej99aaxcs6g6vyfas7k2654w3vrvgs7nl8j".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<IsClaimedResponse>(&res).unwrap(),
+        IsClaimedResponse {
+            is_claimed: true,
+            claim: Some(ClaimRecordResponse {
+                amounts: vec![Uint128::from(1000000u128)],
+                height: 12345,
+                time: mock_env().block.time,
+                recipient: "recipient0000".to_string(),
+            }),
+        }
+    );
+}
+
+#[test]
+fn claim_many() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for merkle_root in [
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95",
+        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37",
+    ] {
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: merkle_root.to_string(),
+            expiration: None,
+            start: None,
+            total_amount: None,
+            asset_info: None,
+            assets: None,
+            activation_height: None,
+            vesting: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let msg = ExecuteMsg::ClaimMany { claims: vec![] };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::NoClaims {});
+
+    let claims = vec![
+        StageClaim {
+            stage: 1u32,
+            amount: Some(Uint128::from(1000001u128)),
+            proof: vec![
+                "b8ee25ffbee5ee215c4ad992fe582f20175868bc310ad9b2b7bdf440a224b2df".to_string(),
+                "98d73e0a035f23c490fef5e307f6e74652b9d3688c2aa5bff70eaa65956a24e1".to_string(),
+                "f328b89c766a62b8f1c768fefa1139c9562c6e05bab57a2af87f35e83f9e9dcf".to_string(),
+                "fe19ca2434f87cadb0431311ac9a484792525eb66a952e257f68bf02b4561950".to_string(),
+            ],
+            amounts: None,
+        },
+        StageClaim {
+            stage: 2u32,
+            amount: Some(Uint128::from(2000001u128)),
+            proof: vec![
+                "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+                "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+                "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+                "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+            ],
+            amounts: None,
+        },
+    ];
+
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let msg = ExecuteMsg::ClaimMany {
+        claims: claims.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "mirror0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                amount: Uint128::from(3000002u128),
+            })
+            .unwrap(),
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_many"),
+            attr("address", "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8"),
+            attr("stages", "1,2"),
+            attr("amount", "3000002"),
+        ]
+    );
+
+    for stage in [1u32, 2u32] {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::IsClaimed {
+                stage,
+                address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(from_binary::<IsClaimedResponse>(&res).unwrap().is_claimed);
+    }
+
+    // a single rejected claim fails the whole batch
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let msg = ExecuteMsg::ClaimMany { claims };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::AlreadyClaimed {
+            stage: 1,
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+        }
+    );
+}
+
+#[test]
+fn claim_status() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for merkle_root in [
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95",
+        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37",
+        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95",
+    ] {
+        let info = mock_info("owner0000", &[]);
+        let msg = ExecuteMsg::RegisterMerkleRoot {
+            merkle_root: merkle_root.to_string(),
+            expiration: None,
+            start: None,
+            total_amount: None,
+            asset_info: None,
+            assets: None,
+            activation_height: None,
+            vesting: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::Claim {
+        amount: Some(Uint128::from(2000001u128)),
+        stage: 2u32,
+        proof: vec![
+            "ca2784085f944e5594bb751c3237d6162f7c2b24480b3a37e9803815b7a5ce42".to_string(),
+            "5b07b5898fc9aa101f27344dab0737aede6c3aa7c9f10b4b1fda6d26eb669b0f".to_string(),
+            "4847b2b9a6432a7bdf2bdafacbbeea3aab18c524024fc6e1bc655e04cbc171f3".to_string(),
+            "cad1958c1a5c815f23450f1a2761a5a75ab2b894a258601bf93cd026469d42f2".to_string(),
+        ],
+        amounts: None,
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ClaimStatus {
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+            start_after: None,
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<ClaimStatusResponse>(&res).unwrap(),
+        ClaimStatusResponse {
+            claims: vec![
+                StageClaimStatus {
+                    stage: 1,
+                    is_claimed: false,
+                },
+                StageClaimStatus {
+                    stage: 2,
+                    is_claimed: true,
+                },
+            ],
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ClaimStatus {
+            address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+            start_after: Some(2),
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<ClaimStatusResponse>(&res).unwrap(),
+        ClaimStatusResponse {
+            claims: vec![StageClaimStatus {
+                stage: 3,
+                is_claimed: false,
+            }],
+        }
+    );
+}
+
+#[test]
+fn all_stages() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: Some(Uint128::from(1500000u128)),
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37".to_string(),
+        expiration: Some(Expiration::AtHeight(12500)),
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: None,
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mirror_token = AssetInfo::Token {
+        contract_addr: "mirror0000".to_string(),
+    };
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AllStages {
+            start_after: None,
+            limit: Some(1),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<AllStagesResponse>(&res).unwrap(),
+        AllStagesResponse {
+            stages: vec![StageResponse {
+                info: StageInfoResponse {
+                    stage: 1,
+                    merkle_root: Some(
+                        "85e33930e7a8f015316cb4a53a4c45d26a69f299fc4c83f17357e1fd62e8fd95"
+                            .to_string()
+                    ),
+                    start: None,
+                    expiration: Expiration::Never {},
+                    asset_infos: vec![mirror_token.clone()],
+                    frozen: false,
+                    vesting: None,
+                },
+                stats: StageStatsResponse {
+                    stage: 1,
+                    assets: vec![StageAssetStats {
+                        asset_info: mirror_token.clone(),
+                        total_amount: Some(Uint128::from(1500000u128)),
+                        claimed_amount: Uint128::zero(),
+                        released_amount: Uint128::zero(),
+                        withdrawn_amount: Uint128::zero(),
+                        remaining_amount: Some(Uint128::from(1500000u128)),
+                    }],
+                    claimant_count: 0,
+                },
+            }],
+        }
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::AllStages {
+            start_after: Some(1),
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<AllStagesResponse>(&res).unwrap(),
+        AllStagesResponse {
+            stages: vec![StageResponse {
+                info: StageInfoResponse {
+                    stage: 2,
+                    merkle_root: Some(
+                        "634de21cde1044f41d90373733b0f0fb1c1c71f9652b905cdf159e73c4cf0d37"
+                            .to_string()
+                    ),
+                    start: None,
+                    expiration: Expiration::AtHeight(12500),
+                    asset_infos: vec![mirror_token.clone()],
+                    frozen: false,
+                    vesting: None,
+                },
+                stats: StageStatsResponse {
+                    stage: 2,
+                    assets: vec![StageAssetStats {
+                        asset_info: mirror_token,
+                        total_amount: None,
+                        claimed_amount: Uint128::zero(),
+                        released_amount: Uint128::zero(),
+                        withdrawn_amount: Uint128::zero(),
+                        remaining_amount: None,
+                    }],
+                    claimant_count: 0,
+                },
+            }],
+        }
+    );
+}
+
+#[test]
+fn claimants() {
+    let mut deps = mock_dependencies(&coins(1000000u128, "uusd"));
+    deps.querier.with_token_balances(&[(
+        &"mirror0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        mirror_token: "mirror0000".to_string(),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("owner0000", &[]);
+    let msg = ExecuteMsg::RegisterMerkleRoot {
+        merkle_root: "85dcb94ae85fc4df0628da4b6b92b1115855ffc6b95bf29831c1119f2c638c99".to_string(),
+        expiration: None,
+        start: None,
+        total_amount: None,
+        asset_info: None,
+        assets: Some(vec![
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "mirror0000".to_string(),
+                },
+                amount: Uint128::from(1500000u128),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(1000000u128),
+            },
+        ]),
+        activation_height: None,
+        vesting: None,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Claim {
+        amount: None,
+        stage: 1u32,
+        proof: vec!["374922da6f327d91c4aa9c5adad3ec90d90cb08ee6bfd591ea0648abe109130b".to_string()],
+        amounts: Some(vec![Uint128::from(500000u128), Uint128::from(1000000u128)]),
+        authorization: None,
+    };
+    let info = mock_info("terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Claim {
+        amount: None,
+        stage: 1u32,
+        proof: vec!["4bf16f3e82304ea25ec7cbac90cd55664e20d81dc4cecbfbadfe4a2e3a456639".to_string()],
+        amounts: Some(vec![Uint128::from(1000000u128), Uint128::zero()]),
+        authorization: None,
+    };
+    let mut env = mock_env();
+    env.block.height += 10;
+    let info = mock_info("addr0000", &[]);
+    let _res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    let mut claimants: Vec<ClaimantResponse> = vec![];
+    let mut start_after: Option<String> = None;
+    loop {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Claimants {
+                stage: 1,
+                start_after: start_after.clone(),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+        let page = from_binary::<ClaimantsResponse>(&res).unwrap().claimants;
+        match page.last() {
+            Some(claimant) => start_after = Some(claimant.address.clone()),
+            None => break,
+        }
+        assert_eq!(page.len(), 1);
+        claimants.extend(page);
+    }
+
+    claimants.sort_by(|a, b| a.address.cmp(&b.address));
+    assert_eq!(
+        claimants,
+        vec![
+            ClaimantResponse {
+                address: "addr0000".to_string(),
+                claim: Some(ClaimRecordResponse {
+                    amounts: vec![Uint128::from(1000000u128), Uint128::zero()],
+                    height: 12355,
+                    time: mock_env().block.time,
+                    recipient: "addr0000".to_string(),
+                }),
+            },
+            ClaimantResponse {
+                address: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                claim: Some(ClaimRecordResponse {
+                    amounts: vec![Uint128::from(500000u128), Uint128::from(1000000u128)],
+                    height: 12345,
+                    time: mock_env().block.time,
+                    recipient: "terra1qfqa2eu9wp272ha93lj4yhcenrc6ymng079nu8".to_string(),
+                }),
+            },
+        ]
+    );
 }